@@ -1,53 +1,106 @@
-use crate::config::ConfigManager;
+use crate::fuzzy::fuzzy_match;
 use ratatui::widgets::ListState;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+use tmucks::check::Issue;
+use tmucks::config::{ActiveProfile, ApplyMode, ConfigManager};
+use tmucks::diff::{unified_diff, DiffLine};
+use tmucks::error::{Error, Result};
+use tmucks::meta::ProfileMeta;
+use tmucks::settings::display_path;
+use tmucks::tmux::{self, Reload, ReloadTargets, Reloads};
+
 #[derive(PartialEq)]
 pub enum InputMode {
     Normal,
+    Searching,
     Saving,
+    Renaming,
+    Copying,
     UpdateConfirm,
+    ReapplyConfirm,
+    DeleteConfirm,
+    Backups,
+    Diff,
+    ReloadErrors,
+    Servers,
+}
+
+/// A saved config shown in the list
+pub struct ListedConfig {
+    pub name: String,
+    /// Char indices of the name that matched the search
+    pub positions: Vec<usize>,
 }
 
 pub struct App {
     pub config_manager: ConfigManager,
+    /// The configs matching the search, in the order shown. `list_state`
+    /// indexes into this, not into `config_manager.configs`.
+    pub listed: Vec<ListedConfig>,
     pub list_state: ListState,
+    /// Words starting with `#` filter by tag, the rest fuzzy-matches names
+    pub search_query: String,
     pub status_message: String,
     pub input_mode: InputMode,
     pub input_buffer: String,
+    /// The name typed into a rename or copy prompt is taken, and pressing
+    /// enter again will overwrite it
+    pub overwrite_pending: bool,
     pub pending_update_config: Option<String>,
     pub status_message_time: Option<Instant>,
     pub default_status_message: String,
+    pub backups: Vec<String>,
+    pub backup_list_state: ListState,
+    pub active_profile: Option<ActiveProfile>,
+    pub diff_lines: Vec<DiffLine>,
+    pub diff_scroll: u16,
+    pub preview_scroll: u16,
+    pub reload_errors: Vec<Issue>,
+    /// Live tmux sockets and whether each gets reloaded
+    pub sockets: Vec<(PathBuf, bool)>,
+    pub socket_list_state: ListState,
 }
 
 impl App {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let config_manager = ConfigManager::new()?;
-        let mut list_state = ListState::default();
-        if !config_manager.configs.is_empty() {
-            list_state.select(Some(0));
-        }
-        let default_status_message = String::from("use j/k to navigate, enter to apply config, s to save current, u to update existing, d to delete, q to quit");
-        Ok(Self {
+    pub fn new(config_manager: ConfigManager) -> Result<Self> {
+        let active_profile = config_manager.active_profile();
+        let default_status_message = String::from("use j/k to navigate, / to search, J/K to scroll preview, enter to apply config, F to force apply, s to save current, u to update existing, e to edit, r to rename, c to copy, d to delete, v to diff, z to undo, b for backups, t for tmux servers, q to quit");
+        let mut app = Self {
             config_manager,
-            list_state,
+            listed: Vec::new(),
+            list_state: ListState::default(),
+            search_query: String::new(),
             status_message: default_status_message.clone(),
             input_mode: InputMode::Normal,
             input_buffer: String::new(),
+            overwrite_pending: false,
             pending_update_config: None,
             status_message_time: None,
             default_status_message,
-        })
+            backups: Vec::new(),
+            backup_list_state: ListState::default(),
+            active_profile,
+            diff_lines: Vec::new(),
+            diff_scroll: 0,
+            preview_scroll: 0,
+            reload_errors: Vec::new(),
+            sockets: Vec::new(),
+            socket_list_state: ListState::default(),
+        };
+        app.apply_search();
+        Ok(app)
     }
 
     pub fn next(&mut self) {
-        if self.config_manager.configs.is_empty() {
+        if self.listed.is_empty() {
             return;
         }
-        
+
         let i = match self.list_state.selected() {
             Some(i) => {
-                if i >= self.config_manager.configs.len() - 1 {
+                if i >= self.listed.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -56,17 +109,18 @@ impl App {
             None => 0,
         };
         self.list_state.select(Some(i));
+        self.preview_scroll = 0;
     }
 
     pub fn previous(&mut self) {
-        if self.config_manager.configs.is_empty() {
+        if self.listed.is_empty() {
             return;
         }
-        
+
         let i = match self.list_state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.config_manager.configs.len() - 1
+                    self.listed.len() - 1
                 } else {
                     i - 1
                 }
@@ -74,65 +128,315 @@ impl App {
             None => 0,
         };
         self.list_state.select(Some(i));
+        self.preview_scroll = 0;
     }
 
-    pub fn apply_config(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(config_name) = self.config_manager.configs.get(selected) {
-                self.config_manager.apply_config(config_name)?;
-                self.set_status_message(format!("+ applied config: {}", config_name));
-            }
+    /// Applies the selected config. Without `force` one with errors is
+    /// refused, and so is replacing a symlink out of the store.
+    pub fn apply_config(&mut self, force: bool) -> Result<()> {
+        if let Some(config_name) = self.selected_config() {
+            let reload = match self.config_manager.apply_config(&config_name, force) {
+                Ok(reload) => reload,
+                Err(Error::ConfigErrors { issues, .. }) => {
+                    let first = issues.first().map(ToString::to_string).unwrap_or_default();
+                    self.set_status_message(format!(
+                        "- {} has {} problem(s), {} (F to apply anyway)",
+                        config_name,
+                        issues.len(),
+                        first
+                    ));
+                    return Ok(());
+                }
+                Err(Error::TargetIsLink { path, link }) => {
+                    self.set_status_message(format!(
+                        "- {} is a symlink to {} (F to replace it)",
+                        display_path(&path),
+                        display_path(&link)
+                    ));
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            };
+            self.report_reload(reload, format!("applied config: {}", config_name));
+            self.refresh_active_profile();
         }
         Ok(())
     }
 
-    pub fn delete_config(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(config_name) = self.config_manager.configs.get(selected).cloned() {
-                self.config_manager.delete_config(&config_name)?;
-                self.set_status_message(format!("+ deleted config: {}", config_name));
-                
-                // Refresh config list
-                self.config_manager = ConfigManager::new()?;
-                if self.config_manager.configs.is_empty() {
-                    self.list_state.select(None);
-                } else if selected >= self.config_manager.configs.len() {
-                    self.list_state.select(Some(self.config_manager.configs.len() - 1));
-                }
+    /// Opens the search prompt, starting from the current query
+    pub fn start_search(&mut self) {
+        self.input_mode = InputMode::Searching;
+    }
+
+    /// Re-filters the list after the query was typed into, selecting the
+    /// best match
+    pub fn update_search(&mut self) {
+        self.apply_search();
+        if !self.listed.is_empty() {
+            self.list_state.select(Some(0));
+        }
+        self.preview_scroll = 0;
+    }
+
+    /// Drops the search and shows every config again, keeping the selection
+    pub fn clear_search(&mut self) {
+        self.search_query.clear();
+        self.input_mode = InputMode::Normal;
+        self.apply_search();
+    }
+
+    /// Rebuilds `listed` from the saved configs and the search query, best
+    /// match first. The selected config stays selected if it's still listed.
+    pub fn apply_search(&mut self) {
+        let selected = self.selected_config();
+        let selected_index = self.list_state.selected().unwrap_or(0);
+
+        let mut tags = Vec::new();
+        let mut name_query = String::new();
+        for word in self.search_query.split_whitespace() {
+            match word.strip_prefix('#') {
+                // A lone `#` is a tag still being typed
+                Some("") => {}
+                Some(tag) => tags.push(tag.to_lowercase()),
+                None => name_query.push_str(word),
             }
         }
+
+        let mut matches: Vec<(i32, ListedConfig)> = self
+            .config_manager
+            .configs
+            .iter()
+            .filter_map(|name| {
+                if !tags.is_empty() {
+                    let meta = self.config_manager.config_meta(name).unwrap_or_default();
+                    let tagged = tags.iter().all(|wanted| {
+                        meta.tags
+                            .iter()
+                            .any(|tag| tag.to_lowercase().starts_with(wanted))
+                    });
+                    if !tagged {
+                        return None;
+                    }
+                }
+                let found = fuzzy_match(&name_query, name)?;
+                Some((
+                    found.score,
+                    ListedConfig {
+                        name: name.clone(),
+                        positions: found.positions,
+                    },
+                ))
+            })
+            .collect();
+        // Stable, so equal scores keep the alphabetical order
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.listed = matches.into_iter().map(|(_, listed)| listed).collect();
+
+        if self.listed.is_empty() {
+            self.list_state.select(None);
+        } else {
+            let position = selected
+                .and_then(|name| self.listed.iter().position(|c| c.name == name))
+                .unwrap_or(selected_index.min(self.listed.len() - 1));
+            self.list_state.select(Some(position));
+        }
+    }
+
+    /// Selects a config by name, if it's listed
+    fn select_config(&mut self, name: &str) {
+        if let Some(position) = self.listed.iter().position(|c| c.name == name) {
+            self.list_state.select(Some(position));
+        }
+    }
+
+    pub fn start_delete(&mut self) {
+        if self.selected_config().is_some() {
+            self.input_mode = InputMode::DeleteConfirm;
+        }
+    }
+
+    pub fn delete_config(&mut self) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+        if let Some(config_name) = self.selected_config() {
+            self.config_manager.delete_config(&config_name)?;
+            self.set_status_message(format!("+ moved config to trash: {}", config_name));
+
+            // Refresh config list, the next one along takes its place
+            self.config_manager.refresh()?;
+            self.apply_search();
+            self.refresh_active_profile();
+            self.preview_scroll = 0;
+        }
         Ok(())
     }
 
-    pub fn save_current_config(&mut self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        self.config_manager.save_current_config(name)?;
+    /// Saves the tmux config under a new name. If the name is taken, offers
+    /// to update that config instead.
+    pub fn save_current_config(&mut self, name: &str) -> Result<()> {
+        let saved = self
+            .config_manager
+            .save_current_config(name, &ProfileMeta::new(None, Vec::new()));
+        if let Err(Error::ConfigExists(existing)) = saved {
+            self.select_config(&existing);
+            self.start_update(existing);
+            return Ok(());
+        }
+        saved?;
         self.set_status_message(format!("+ saved current config as: {}", name));
-        
+
         // Refresh config list
-        self.config_manager = ConfigManager::new()?;
-        if !self.config_manager.configs.is_empty() {
-            self.list_state.select(Some(0));
+        self.config_manager.refresh()?;
+        self.apply_search();
+        self.select_config(name);
+        self.refresh_active_profile();
+        self.preview_scroll = 0;
+        Ok(())
+    }
+
+    /// Opens the name prompt for renaming or copying the selected config.
+    /// Renaming starts from the current name.
+    pub fn start_name_prompt(&mut self, mode: InputMode) {
+        let Some(config_name) = self.selected_config() else {
+            return;
+        };
+        self.input_buffer = if mode == InputMode::Renaming {
+            config_name.trim_end_matches(".conf").to_string()
+        } else {
+            String::new()
+        };
+        self.overwrite_pending = false;
+        self.input_mode = mode;
+    }
+
+    /// Renames or copies the selected config to `name`, depending on the
+    /// prompt that's open. Returns false without doing anything the first
+    /// time `name` is already taken, so the user can confirm overwriting it.
+    pub fn finish_name_prompt(&mut self, name: &str) -> Result<bool> {
+        let Some(config_name) = self.selected_config() else {
+            return Ok(true);
+        };
+        if !self.overwrite_pending
+            && name != config_name
+            && self.config_manager.configs.iter().any(|c| c == name)
+        {
+            self.overwrite_pending = true;
+            return Ok(false);
+        }
+
+        let force = self.overwrite_pending;
+        if self.input_mode == InputMode::Renaming {
+            self.config_manager
+                .rename_config(&config_name, name, force)?;
+            self.set_status_message(format!("+ renamed {} to {}", config_name, name));
+        } else {
+            self.config_manager.copy_config(&config_name, name, force)?;
+            self.set_status_message(format!("+ copied {} to {}", config_name, name));
+        }
+
+        // Refresh config list and follow the new name
+        self.config_manager.refresh()?;
+        self.apply_search();
+        self.select_config(name);
+        self.refresh_active_profile();
+        self.preview_scroll = 0;
+        Ok(true)
+    }
+
+    /// Picks up the changes after the selected config was edited, offering
+    /// to re-apply it if it's the active one
+    pub fn finish_edit(&mut self, config_name: &str, changed: bool) -> Result<()> {
+        let was_active = self
+            .active_profile
+            .as_ref()
+            .is_some_and(|active| active.name == config_name);
+
+        if changed {
+            self.config_manager.touch_config(config_name)?;
+        }
+        self.config_manager.refresh()?;
+        self.apply_search();
+        self.refresh_active_profile();
+        self.preview_scroll = 0;
+
+        if !changed {
+            self.set_status_message(format!("= no changes to {}", config_name));
+        } else if was_active {
+            self.input_mode = InputMode::ReapplyConfirm;
+        } else {
+            self.set_status_message(format!("+ edited config: {}", config_name));
         }
         Ok(())
     }
 
+    /// Re-applies the selected config after editing it. A tmux config that
+    /// is symlinked to it stays a symlink.
+    pub fn reapply_config(&mut self) -> Result<()> {
+        self.input_mode = InputMode::Normal;
+        let apply_mode = self.config_manager.apply_mode;
+        if self.config_manager.linked_profile().is_some()
+            && self.config_manager.linked_profile() == self.selected_config()
+        {
+            self.config_manager.apply_mode = ApplyMode::Symlink;
+        }
+        let result = self.apply_config(false);
+        self.config_manager.apply_mode = apply_mode;
+        result
+    }
+
+    pub fn selected_config(&self) -> Option<String> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.listed.get(i))
+            .map(|listed| listed.name.clone())
+    }
+
     pub fn start_update_mode(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(config_name) = self.config_manager.configs.get(selected) {
-                self.pending_update_config = Some(config_name.clone());
-                self.input_mode = InputMode::UpdateConfirm;
-            } else {
-                self.set_status_message(String::from("- no config selected to update"));
-            }
+        if let Some(config_name) = self.selected_config() {
+            self.start_update(config_name);
         } else {
             self.set_status_message(String::from("- no config selected to update"));
         }
     }
 
-    pub fn confirm_update(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Asks whether to update `config_name` with the tmux config
+    fn start_update(&mut self, config_name: String) {
+        // Show what the update would change in the saved config, with
+        // templates as they would be applied
+        self.diff_lines = match (
+            self.config_manager.render_config(&config_name),
+            self.config_manager.read_target(),
+        ) {
+            (Ok(saved), Ok(current)) => unified_diff(
+                &saved,
+                &current,
+                &config_name,
+                &self.config_manager.target_display(),
+            ),
+            _ => Vec::new(),
+        };
+        self.diff_scroll = 0;
+        self.pending_update_config = Some(config_name);
+        self.input_mode = InputMode::UpdateConfirm;
+    }
+
+    pub fn confirm_update(&mut self) -> Result<()> {
         if let Some(config_name) = self.pending_update_config.take() {
+            if self.config_manager.linked_profile().as_deref() == Some(config_name.as_str()) {
+                self.set_status_message(format!(
+                    "= {} is symlinked to '{}', it is already up to date",
+                    self.config_manager.target_display(),
+                    config_name
+                ));
+                self.input_mode = InputMode::Normal;
+                return Ok(());
+            }
             self.config_manager.update_config(&config_name)?;
-            self.set_status_message(format!("+ updated config '{}' with current ~/.tmux.conf", config_name));
+            self.set_status_message(format!(
+                "+ updated config '{}' with current {}",
+                config_name,
+                self.config_manager.target_display()
+            ));
+            self.refresh_active_profile();
         }
         self.input_mode = InputMode::Normal;
         Ok(())
@@ -145,6 +449,273 @@ impl App {
         self.status_message_time = None;
     }
 
+    pub fn scroll_preview_down(&mut self) {
+        self.preview_scroll = self.preview_scroll.saturating_add(1);
+    }
+
+    pub fn scroll_preview_up(&mut self) {
+        self.preview_scroll = self.preview_scroll.saturating_sub(1);
+    }
+
+    /// Shows the diff from the selected config, as it would be applied, to
+    /// the live tmux config
+    pub fn open_diff(&mut self) -> Result<()> {
+        if let Some(config_name) = self.selected_config() {
+            let saved = self.config_manager.render_config(&config_name)?;
+            let current = self.config_manager.read_target()?;
+            self.diff_lines = unified_diff(
+                &saved,
+                &current,
+                &config_name,
+                &self.config_manager.target_display(),
+            );
+            self.diff_scroll = 0;
+            self.input_mode = InputMode::Diff;
+        }
+        Ok(())
+    }
+
+    pub fn close_diff(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn scroll_diff_down(&mut self) {
+        if (self.diff_scroll as usize) + 1 < self.diff_lines.len() {
+            self.diff_scroll += 1;
+        }
+    }
+
+    pub fn scroll_diff_up(&mut self) {
+        self.diff_scroll = self.diff_scroll.saturating_sub(1);
+    }
+
+    pub fn undo_apply(&mut self) -> Result<()> {
+        let undone = self.config_manager.undo(1)?;
+        match undone.reloads {
+            Some(reload) => {
+                let restored = match (&undone.backup, &undone.link) {
+                    (_, Some(link)) => format!("link to {}", display_path(link)),
+                    (Some(backup), None) => backup.clone(),
+                    (None, None) => String::new(),
+                };
+                self.report_reload(reload, format!("undid last apply, restored: {}", restored))
+            }
+            None => {
+                let message = format!(
+                    "+ undid last apply, removed {}",
+                    self.config_manager.target_display()
+                );
+                self.set_status_message(message)
+            }
+        }
+        self.refresh_active_profile();
+        Ok(())
+    }
+
+    pub fn open_backups(&mut self) -> Result<()> {
+        self.backups = self.config_manager.list_backups()?;
+        if self.backups.is_empty() {
+            self.backup_list_state.select(None);
+        } else {
+            self.backup_list_state.select(Some(0));
+        }
+        self.input_mode = InputMode::Backups;
+        Ok(())
+    }
+
+    pub fn close_backups(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn next_backup(&mut self) {
+        if self.backups.is_empty() {
+            return;
+        }
+
+        let i = match self.backup_list_state.selected() {
+            Some(i) => {
+                if i >= self.backups.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.backup_list_state.select(Some(i));
+    }
+
+    pub fn previous_backup(&mut self) {
+        if self.backups.is_empty() {
+            return;
+        }
+
+        let i = match self.backup_list_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.backups.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.backup_list_state.select(Some(i));
+    }
+
+    pub fn selected_backup(&self) -> Option<&String> {
+        self.backup_list_state
+            .selected()
+            .and_then(|i| self.backups.get(i))
+    }
+
+    pub fn restore_backup(&mut self) -> Result<()> {
+        if let Some(backup_name) = self.selected_backup().cloned() {
+            let reload = self.config_manager.restore_backup(&backup_name)?;
+            self.refresh_active_profile();
+
+            // Restoring takes a fresh backup, so refresh the list
+            self.open_backups()?;
+            self.report_reload(reload, format!("restored backup: {}", backup_name));
+        }
+        Ok(())
+    }
+
+    /// Sets the status for a change that reloaded tmux. Errors from tmux
+    /// open a popup listing them.
+    fn report_reload(&mut self, reloads: Reloads, done: String) {
+        let mut reloaded = 0;
+        let mut not_installed = false;
+        let mut failed = 0;
+        let mut errors = Vec::new();
+        for (_, reload) in reloads {
+            match reload {
+                Reload::Reloaded => reloaded += 1,
+                Reload::NotInstalled => not_installed = true,
+                Reload::NoServer => {}
+                Reload::ConfigErrors(issues) => {
+                    failed += 1;
+                    // Servers mostly agree on errors, so show the first set
+                    if errors.is_empty() {
+                        errors = issues;
+                    }
+                }
+            }
+        }
+
+        if failed > 0 {
+            self.set_status_message(format!(
+                "- {}, but tmux reported {} error(s){}",
+                done,
+                errors.len(),
+                if failed > 1 {
+                    format!(" on {} servers", failed)
+                } else {
+                    String::new()
+                }
+            ));
+            self.reload_errors = errors;
+            self.input_mode = InputMode::ReloadErrors;
+        } else if not_installed {
+            self.set_status_message(format!("+ {} (tmux not installed, not reloaded)", done));
+        } else if reloaded == 0 {
+            self.set_status_message(format!("+ {} (no tmux server running)", done));
+        } else if reloaded == 1 {
+            self.set_status_message(format!("+ {}", done));
+        } else {
+            self.set_status_message(format!("+ {} (reloaded {} servers)", done, reloaded));
+        }
+    }
+
+    pub fn close_reload_errors(&mut self) {
+        self.reload_errors.clear();
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Lists the live tmux sockets, ticking the ones that currently get
+    /// reloaded
+    pub fn open_servers(&mut self) {
+        let default_socket = tmux::default_socket();
+        self.sockets = tmux::find_sockets()
+            .into_iter()
+            .map(|socket| {
+                let checked = match &self.config_manager.reload_targets {
+                    ReloadTargets::Default => socket == default_socket,
+                    ReloadTargets::Sockets(sockets) => sockets.contains(&socket),
+                    ReloadTargets::All => true,
+                };
+                (socket, checked)
+            })
+            .collect();
+        if self.sockets.is_empty() {
+            self.socket_list_state.select(None);
+        } else {
+            self.socket_list_state.select(Some(0));
+        }
+        self.input_mode = InputMode::Servers;
+    }
+
+    /// Reloads the ticked sockets from now on
+    pub fn close_servers(&mut self) {
+        let checked: Vec<PathBuf> = self
+            .sockets
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(socket, _)| socket.clone())
+            .collect();
+        self.set_status_message(format!("= reloading {} tmux server(s)", checked.len()));
+        self.config_manager.reload_targets = ReloadTargets::Sockets(checked);
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn next_server(&mut self) {
+        if self.sockets.is_empty() {
+            return;
+        }
+
+        let i = match self.socket_list_state.selected() {
+            Some(i) => {
+                if i >= self.sockets.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.socket_list_state.select(Some(i));
+    }
+
+    pub fn previous_server(&mut self) {
+        if self.sockets.is_empty() {
+            return;
+        }
+
+        let i = match self.socket_list_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.sockets.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.socket_list_state.select(Some(i));
+    }
+
+    pub fn toggle_server(&mut self) {
+        if let Some(i) = self.socket_list_state.selected() {
+            if let Some((_, checked)) = self.sockets.get_mut(i) {
+                *checked = !*checked;
+            }
+        }
+    }
+
+    pub fn refresh_active_profile(&mut self) {
+        self.active_profile = self.config_manager.active_profile();
+    }
+
     pub fn set_status_message(&mut self, message: String) {
         self.status_message = message;
         self.status_message_time = Some(Instant::now());
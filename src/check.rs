@@ -0,0 +1,173 @@
+//! Checking tmux configs for problems before they are applied.
+
+use std::{fmt, fs, path::Path};
+
+use crate::error::Result;
+use crate::parser::{self, ConfigFile, Directive, LineKind};
+use crate::tmux::{self, ThrowawayServer, Tmux};
+
+/// What found an `Issue`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IssueSource {
+    /// Found by tmucks' own parser
+    Syntax,
+    /// Reported by tmux during the dry run
+    Tmux,
+}
+
+/// A problem found in a config
+#[derive(Clone, Debug)]
+pub struct Issue {
+    /// 1-based line number, when the problem could be located
+    pub line: Option<usize>,
+    /// What is wrong, as the parser or tmux put it
+    pub message: String,
+    /// Whether the parser or tmux found it
+    pub source: IssueSource,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Validates a tmux config file. A static syntax pass always runs; when tmux
+/// is installed the file is also sourced into a throwaway server on a private
+/// socket, and tmux's verdict wins for the lines it reports on.
+pub fn check_file(path: &Path, tmux: &Tmux) -> Result<Vec<Issue>> {
+    let contents = fs::read_to_string(path)?;
+    let parsed = parser::parse(&contents);
+
+    let mut issues: Vec<Issue> = parsed
+        .errors
+        .iter()
+        .map(|error| Issue {
+            line: Some(error.line),
+            message: error.message.clone(),
+            source: IssueSource::Syntax,
+        })
+        .collect();
+
+    if tmux.is_installed() {
+        let tmux_issues = dry_run(path, &parsed, tmux)?;
+        issues.retain(|issue| !tmux_issues.iter().any(|t| t.line == issue.line));
+        issues.extend(tmux_issues);
+    }
+
+    // Located issues first, in line order
+    issues.sort_by_key(|issue| (issue.line.is_none(), issue.line));
+    Ok(issues)
+}
+
+/// Validates config text that isn't saved as it is, like a rendered
+/// template, by checking it from a scratch file in a private directory
+pub fn check_text(contents: &str, tmux: &Tmux) -> Result<Vec<Issue>> {
+    let scratch_dir = tempfile::Builder::new()
+        .prefix("tmucks-render-")
+        .tempdir()?;
+    let path = scratch_dir.path().join("rendered.conf");
+    fs::write(&path, contents)?;
+    check_file(&path, tmux)
+}
+
+fn dry_run(path: &Path, parsed: &ConfigFile, tmux: &Tmux) -> Result<Vec<Issue>> {
+    let server = ThrowawayServer::start(tmux)?;
+    let (success, output) = server.source(path)?;
+    if success {
+        return Ok(Vec::new());
+    }
+
+    let errors = tmux::parse_source_errors(&output, path);
+    locate_with(&server, parsed, errors)
+}
+
+/// Turns errors reported by `tmux source-file` into issues, working out the
+/// line for those tmux didn't give one for. Errors that can't be placed are
+/// kept without a line.
+pub fn locate_errors(path: &Path, errors: Vec<(Option<usize>, String)>, tmux: &Tmux) -> Vec<Issue> {
+    let all_located = errors.iter().all(|(line, _)| line.is_some());
+    let located = (!all_located && tmux.is_installed())
+        .then(|| {
+            let parsed = parser::parse(&fs::read_to_string(path).ok()?);
+            let server = ThrowawayServer::start(tmux).ok()?;
+            locate_with(&server, &parsed, errors.clone()).ok()
+        })
+        .flatten();
+
+    located.unwrap_or_else(|| {
+        errors
+            .into_iter()
+            .map(|(line, message)| Issue {
+                line,
+                message,
+                source: IssueSource::Tmux,
+            })
+            .collect()
+    })
+}
+
+/// An issue from tmux that isn't tied to a line
+pub fn unlocated_issue(message: String) -> Issue {
+    Issue {
+        line: None,
+        message,
+        source: IssueSource::Tmux,
+    }
+}
+
+fn locate_with(
+    server: &ThrowawayServer,
+    parsed: &ConfigFile,
+    errors: Vec<(Option<usize>, String)>,
+) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    let mut unlocated = Vec::new();
+    for (line, message) in errors {
+        match line {
+            Some(_) => issues.push(Issue {
+                line,
+                message,
+                source: IssueSource::Tmux,
+            }),
+            None => unlocated.push(message),
+        }
+    }
+
+    // Errors raised while running commands come without line numbers, so
+    // find them by sourcing each top-level line on its own. Lines inside
+    // %if blocks are skipped since their condition may not hold.
+    let mut depth: usize = 0;
+    for line in &parsed.lines {
+        if unlocated.is_empty() {
+            break;
+        }
+        match &line.kind {
+            LineKind::Directive(Directive::If(_)) => depth += 1,
+            LineKind::Directive(Directive::Endif) => depth = depth.saturating_sub(1),
+            LineKind::Commands { .. } if depth == 0 => {
+                let (success, output) = server.source_text(&line.raw)?;
+                if success {
+                    continue;
+                }
+                for message in output.lines().map(str::trim) {
+                    if let Some(i) = unlocated.iter().position(|m| m == message) {
+                        issues.push(Issue {
+                            line: Some(line.number),
+                            message: unlocated.remove(i),
+                            source: IssueSource::Tmux,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    issues.extend(unlocated.into_iter().map(unlocated_issue));
+    issues.sort_by_key(|issue| (issue.line.is_none(), issue.line));
+    Ok(issues)
+}
@@ -1,9 +1,31 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::output::EXIT_CODES_HELP;
+use tmucks::tmux::{self, ReloadTargets};
 
 #[derive(Parser)]
 #[command(name = "tmucks")]
 #[command(about = "Tmux config manager", long_about = None)]
+#[command(after_help = EXIT_CODES_HELP)]
 pub struct Cli {
+    /// Directory holding saved configs (defaults to ~/.config/tmucks)
+    #[arg(long, global = true, env = "TMUCKS_HOME")]
+    pub store: Option<PathBuf>,
+
+    /// tmux config file to manage (defaults to the one tmux loads)
+    #[arg(long, global = true, env = "TMUCKS_TARGET")]
+    pub target: Option<PathBuf>,
+
+    /// Print results and errors as a single JSON object
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Set a template variable, overriding the variables file and
+    /// TMUCKS_VAR_<NAME>. Can be repeated.
+    #[arg(long = "set", global = true, value_name = "NAME=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -11,15 +33,294 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// List all saved configs
-    List,
+    List {
+        /// Show each config's description, tags and history
+        #[arg(short, long)]
+        long: bool,
+    },
     /// Apply a config by name
-    Apply { name: String },
+    Apply {
+        #[arg(required_unless_present = "auto", conflicts_with = "auto")]
+        name: Option<String>,
+        /// Apply the config picked by the settings file's rules for this machine
+        #[arg(long)]
+        auto: bool,
+        /// Symlink the tmux config to the saved config instead of copying it
+        #[arg(long, conflicts_with = "copy")]
+        symlink: bool,
+        /// Copy the saved config over the tmux config
+        #[arg(long)]
+        copy: bool,
+        /// Apply even if the config has errors
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        reload: ReloadArgs,
+    },
+    /// Apply the config picked by the settings file's rules for this machine
+    Auto {
+        /// Show which rule matches without applying anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Apply even if the config has errors
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        reload: ReloadArgs,
+    },
+    /// Check a config for errors without applying it
+    Check { name: String },
+    /// Print a config with its template variables filled in, without applying it
+    Render { name: String },
     /// Save current tmux config with a name
-    Save { name: String },
+    Save {
+        name: String,
+        /// What the config is for
+        #[arg(long)]
+        description: Option<String>,
+        /// Tag to file the config under. Can be repeated.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+    },
+    /// Save the settings of the running tmux server as a new config,
+    /// keeping only what differs from tmux's defaults
+    Snapshot {
+        name: String,
+        /// Server to snapshot, by name (as with tmux -L) or path (as with
+        /// tmux -S)
+        #[arg(long, value_name = "SOCKET")]
+        socket: Option<String>,
+        /// What the config is for
+        #[arg(long)]
+        description: Option<String>,
+        /// Tag to file the config under. Can be repeated.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+    },
     /// Update an existing config with current tmux config
     Update { name: String },
-    /// Delete a config by name
+    /// Move a config to the trash
     Delete { name: String },
+    /// Open a saved config in $VISUAL or $EDITOR
+    Edit { name: String },
+    /// Rename a saved config
+    Rename {
+        old: String,
+        new: String,
+        /// Replace an existing config with the new name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Copy a saved config to a new name
+    Copy {
+        source: String,
+        dest: String,
+        /// Replace an existing config with the destination name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Apply the nearest .tmucks file to the current tmux session only, or
+    /// revert the session once outside the project
+    Project {
+        /// tmux session to use instead of the current one
+        #[arg(long)]
+        session: Option<String>,
+        /// Print shell code that runs this whenever the directory changes
+        #[arg(long, value_name = "SHELL")]
+        hook: Option<Shell>,
+    },
+    /// Try a config in one tmux session, leaving the tmux config alone
+    Try {
+        name: String,
+        /// tmux session to use instead of the current one
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Put back the options a session had before `tmucks try`
+    Untry {
+        /// tmux session to use instead of the current one
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Show a diff between two configs, or a config and the tmux config
+    Diff {
+        name: String,
+        /// Config to compare against (defaults to the tmux config)
+        other: Option<String>,
+        /// Disable coloured output
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Undo the last apply, restoring the previous tmux config
+    Undo {
+        /// Number of applies to go back
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+        #[command(flatten)]
+        reload: ReloadArgs,
+    },
+    /// Manage automatic backups of the tmux config
+    Backups {
+        #[command(subcommand)]
+        command: Option<BackupCommands>,
+    },
+    /// Manage deleted configs
+    Trash {
+        #[command(subcommand)]
+        command: Option<TrashCommands>,
+    },
+}
+
+impl Commands {
+    /// The command as named in JSON output
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::List { .. } => "list",
+            Commands::Apply { .. } => "apply",
+            Commands::Auto { .. } => "auto",
+            Commands::Check { .. } => "check",
+            Commands::Render { .. } => "render",
+            Commands::Save { .. } => "save",
+            Commands::Snapshot { .. } => "snapshot",
+            Commands::Update { .. } => "update",
+            Commands::Delete { .. } => "delete",
+            Commands::Edit { .. } => "edit",
+            Commands::Rename { .. } => "rename",
+            Commands::Copy { .. } => "copy",
+            Commands::Project { .. } => "project",
+            Commands::Try { .. } => "try",
+            Commands::Untry { .. } => "untry",
+            Commands::Diff { .. } => "diff",
+            Commands::Undo { .. } => "undo",
+            Commands::Backups { command } => match command {
+                None | Some(BackupCommands::List) => "backups list",
+                Some(BackupCommands::Show { .. }) => "backups show",
+                Some(BackupCommands::Restore { .. }) => "backups restore",
+                Some(BackupCommands::Prune { .. }) => "backups prune",
+            },
+            Commands::Trash { command } => match command {
+                None | Some(TrashCommands::List) => "trash list",
+                Some(TrashCommands::Restore { .. }) => "trash restore",
+                Some(TrashCommands::Empty) => "trash empty",
+            },
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum TrashCommands {
+    /// List deleted configs, most recently deleted first
+    List,
+    /// Put a deleted config back in the store
+    Restore {
+        name: String,
+        /// Replace an existing config with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Permanently remove everything in the trash
+    Empty,
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommands {
+    /// List backups, newest first
+    List,
+    /// Print the contents of a backup
+    Show { name: String },
+    /// Restore a backup to the tmux config
+    Restore {
+        name: String,
+        #[command(flatten)]
+        reload: ReloadArgs,
+    },
+    /// Remove old backups, keeping the newest ones
+    Prune {
+        /// Number of backups to keep (defaults to the settings file's
+        /// backup_limit, or 20)
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Shell code that keeps the current tmux session in step with the
+    /// project the shell is in
+    pub fn project_hook(self) -> &'static str {
+        match self {
+            Shell::Bash => {
+                r#"_tmucks_project() {
+  if [ -n "$TMUX" ] && [ "$PWD" != "$_tmucks_project_pwd" ]; then
+    _tmucks_project_pwd=$PWD
+    tmucks project >/dev/null
+  fi
+}
+PROMPT_COMMAND="_tmucks_project${PROMPT_COMMAND:+;$PROMPT_COMMAND}"
+"#
+            }
+            Shell::Zsh => {
+                r#"_tmucks_project() {
+  [[ -n "$TMUX" ]] && tmucks project >/dev/null
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _tmucks_project
+_tmucks_project
+"#
+            }
+            Shell::Fish => {
+                r#"function _tmucks_project --on-variable PWD
+    set -q TMUX; and tmucks project >/dev/null
+end
+_tmucks_project
+"#
+            }
+        }
+    }
+}
+
+/// Which tmux servers to reload once the tmux config has changed
+#[derive(Args)]
+pub struct ReloadArgs {
+    /// Reload the server on this socket, by name (as with tmux -L) or path
+    /// (as with tmux -S). Can be repeated.
+    #[arg(long = "socket", value_name = "SOCKET", conflicts_with = "all_sockets")]
+    pub sockets: Vec<String>,
+    /// Reload every running tmux server found in the socket directories
+    #[arg(long)]
+    pub all_sockets: bool,
+}
+
+impl ReloadArgs {
+    pub fn targets(&self) -> ReloadTargets {
+        if self.all_sockets {
+            ReloadTargets::All
+        } else if !self.sockets.is_empty() {
+            ReloadTargets::Sockets(
+                self.sockets
+                    .iter()
+                    .map(|socket| tmux::resolve_socket(socket))
+                    .collect(),
+            )
+        } else {
+            ReloadTargets::Default
+        }
+    }
+}
+
+fn parse_variable(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err("expected NAME=VALUE".to_string()),
+    }
 }
 
 pub fn ensure_conf_extension(name: String) -> String {
@@ -1,36 +1,269 @@
-use std::{fs, path::PathBuf};
+//! The profile store and the tmux config it manages.
 
+use chrono::Local;
+use serde::Deserialize;
+use std::{
+    env, fs,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+};
+
+use crate::check::{check_file, check_text, Issue};
+use crate::error::{Error, Result};
+use crate::meta::{meta_path, ProfileMeta};
+use crate::rules::{self, Environment, Rule};
+use crate::session::Session;
+use crate::settings::{display_path, expand_home, Settings};
+use crate::snapshot;
+use crate::template::{self, Variables};
+use crate::tmux::{ReloadTargets, Reloads, Server, Tmux};
+
+/// How many snapshots of the tmux config are kept before the oldest get
+/// pruned, unless the settings file sets `backup_limit`
+pub const DEFAULT_BACKUP_LIMIT: usize = 20;
+
+/// Undo stack entry recorded when an apply created the tmux config from nothing
+const UNDO_NO_CONFIG: &str = "-";
+
+/// How a profile is put in place at the tmux config path
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ApplyMode {
+    /// Copy the profile over the tmux config
+    #[default]
+    Copy,
+    /// Make the tmux config a symlink to the profile in the store
+    Symlink,
+}
+
+/// The saved config the tmux config was last applied from
+pub struct ActiveProfile {
+    /// The saved config's name
+    pub name: String,
+    /// The tmux config no longer matches the saved config
+    pub modified: bool,
+}
+
+/// What an undo put back at the tmux config path
+pub struct Undone {
+    /// The backup whose contents were restored, or None if there was no
+    /// tmux config back then
+    pub backup: Option<String>,
+    /// Where the restored tmux config links to, if it was a symlink
+    pub link: Option<PathBuf>,
+    /// How reloading tmux went, or None if the tmux config was removed
+    pub reloads: Option<Reloads>,
+}
+
+/// What was at the tmux config path before an apply, kept on the undo stack
+#[derive(Clone, Debug, PartialEq)]
+struct UndoEntry {
+    backup: Option<String>,
+    /// The symlink's target as it was written, if the tmux config was one
+    link: Option<PathBuf>,
+}
+
+impl UndoEntry {
+    /// Entries are stored a line each: the backup name, or `-` without one,
+    /// then a tab and the link target for a symlink
+    fn parse(line: &str) -> Self {
+        let (backup, link) = match line.split_once('\t') {
+            Some((backup, link)) => (backup, Some(PathBuf::from(link))),
+            None => (line, None),
+        };
+        Self {
+            backup: Some(backup.to_string()).filter(|backup| backup != UNDO_NO_CONFIG),
+            link,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        let backup = self.backup.as_deref().unwrap_or(UNDO_NO_CONFIG);
+        match &self.link {
+            Some(link) => format!("{}\t{}", backup, link.display()),
+            None => backup.to_string(),
+        }
+    }
+}
+
+/// A deleted config waiting in the trash
+pub struct TrashEntry {
+    /// The trash directory holding it, named after when it was deleted
+    pub id: String,
+    /// The config's name, which it gets back when restored
+    pub name: String,
+}
+
+impl TrashEntry {
+    /// When the config was deleted, for display
+    pub fn deleted_at(&self) -> String {
+        let timestamp = self.id.split('_').next().unwrap_or(&self.id);
+        chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d-%H%M%S")
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|_| self.id.clone())
+    }
+}
+
+/// A store of named tmux configs and the tmux config they are applied to.
+///
+/// Saved configs are files in the store directory, named like `work.conf`.
+/// Applying one puts it at the tmux config path, after backing up what was
+/// there and recording an undo step, then reloads the running tmux servers.
+/// The store also holds the backups, the trash, template variables and
+/// each config's metadata.
+///
+/// `new` sets a manager up the way the `tmucks` command does, from the
+/// settings file and the usual tmux config locations. `open` takes both
+/// paths as given, for embedding.
 pub struct ConfigManager {
+    /// Names of the saved configs, sorted. `refresh` re-reads them.
     pub configs: Vec<String>,
+    /// Whether applying copies configs or symlinks them
+    pub apply_mode: ApplyMode,
+    /// The tmux servers reloaded after the tmux config changes
+    pub reload_targets: ReloadTargets,
+    /// How many backups are kept, and so how many applies can be undone
+    pub backup_limit: usize,
+    /// The tmux binary used to check, reload and version configs
+    pub tmux: Tmux,
+    /// Template variables from `--set`, which win over the environment and
+    /// the variables file
+    pub variables: Variables,
+    /// Rules from the settings file picking a config for this machine
+    pub rules: Vec<Rule>,
     config_dir: PathBuf,
+    backup_dir: PathBuf,
+    trash_dir: PathBuf,
+    undo_path: PathBuf,
+    active_path: PathBuf,
+    variables_path: PathBuf,
     tmux_config_path: PathBuf,
 }
 
 impl ConfigManager {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        // Get config directory: ~/.config/tmucks/
-        let home = dirs::home_dir().ok_or("Could not find home directory")?;
-        let config_dir = home.join(".config").join("tmucks");
+    /// `store_override` comes from `--store`/`TMUCKS_HOME` and replaces the
+    /// default ~/.config/tmucks/ store. `target_override` comes from
+    /// `--target`/`TMUCKS_TARGET` and wins over the settings file and the
+    /// detected tmux config location.
+    pub fn new(store_override: Option<PathBuf>, target_override: Option<PathBuf>) -> Result<Self> {
+        // Get config directory: ~/.config/tmucks/ unless overridden
+        let home = dirs::home_dir().ok_or(Error::NoHome)?;
+        let config_dir = match store_override {
+            Some(store) => expand_home(&store.to_string_lossy()),
+            None => home.join(".config").join("tmucks"),
+        };
 
+        // Get tmux config path
+        let settings = Settings::load(&config_dir)?;
+        let xdg_config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from);
+        let tmux_config_path = Self::choose_target(
+            target_override,
+            settings.target.as_deref(),
+            &home,
+            xdg_config_home.as_deref(),
+        );
+
+        let mut config_manager = Self::open(config_dir, tmux_config_path)?;
+        config_manager.apply_mode = settings.apply_mode.unwrap_or_default();
+        config_manager.backup_limit = settings.backup_limit.unwrap_or(DEFAULT_BACKUP_LIMIT);
+        config_manager.rules = settings.rules;
+        Ok(config_manager)
+    }
+
+    /// Opens the store at `config_dir` for managing the tmux config at
+    /// `tmux_config_path`, without looking at the home directory or the
+    /// settings file. The store is created if it doesn't exist.
+    pub fn open(config_dir: PathBuf, tmux_config_path: PathBuf) -> Result<Self> {
         // Create config directory if it doesn't exist
         if !config_dir.exists() {
             fs::create_dir_all(&config_dir)?;
         }
 
-        // Get tmux config path
-        let tmux_config_path = home.join(".tmux.conf");
+        // Backups of the tmux config live in <store>/backups/
+        let backup_dir = config_dir.join("backups");
+
+        // Deleted configs wait in <store>/trash/<timestamp>/ until emptied
+        let trash_dir = config_dir.join("trash");
+
+        // Undo history survives across runs in <store>/state/undo
+        let undo_path = config_dir.join("state").join("undo");
+
+        // The last applied config's name is recorded in <store>/state/active
+        let active_path = config_dir.join("state").join("active");
+
+        // Values for template variables are set in <store>/variables.toml
+        let variables_path = config_dir.join("variables.toml");
 
         // Read available configs
         let configs = Self::read_configs(&config_dir)?;
 
         Ok(Self {
             configs,
+            apply_mode: ApplyMode::default(),
+            reload_targets: ReloadTargets::default(),
+            backup_limit: DEFAULT_BACKUP_LIMIT,
+            tmux: Tmux::default(),
+            variables: Variables::new(),
+            rules: Vec::new(),
             config_dir,
+            backup_dir,
+            trash_dir,
+            undo_path,
+            active_path,
+            variables_path,
             tmux_config_path,
         })
     }
 
-    fn read_configs(dir: &PathBuf) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    /// The tmux config to manage: `target_override`, then the settings
+    /// file's target, then the file tmux loads
+    fn choose_target(
+        target_override: Option<PathBuf>,
+        settings_target: Option<&str>,
+        home: &Path,
+        xdg_config_home: Option<&Path>,
+    ) -> PathBuf {
+        match (target_override, settings_target) {
+            (Some(target), _) => expand_home(&target.to_string_lossy()),
+            (None, Some(target)) => expand_home(target),
+            (None, None) => Self::detect_tmux_config(home, xdg_config_home),
+        }
+    }
+
+    /// Finds the file tmux loads, in the order tmux 3.1+ searches for it,
+    /// falling back to ~/.tmux.conf when none exist yet
+    fn detect_tmux_config(home: &Path, xdg_config_home: Option<&Path>) -> PathBuf {
+        let mut candidates = vec![home.join(".tmux.conf")];
+        if let Some(xdg_config_home) = xdg_config_home {
+            candidates.push(xdg_config_home.join("tmux").join("tmux.conf"));
+        }
+        candidates.push(home.join(".config").join("tmux").join("tmux.conf"));
+
+        candidates
+            .into_iter()
+            .find(|path| path.exists())
+            .unwrap_or_else(|| home.join(".tmux.conf"))
+    }
+
+    /// Re-reads the list of saved configs from disk
+    pub fn refresh(&mut self) -> Result<()> {
+        self.configs = Self::read_configs(&self.config_dir)?;
+        Ok(())
+    }
+
+    /// The managed tmux config path, with the home directory shortened to `~`
+    pub fn target_display(&self) -> String {
+        display_path(&self.tmux_config_path)
+    }
+
+    /// The profile store directory, with the home directory shortened to `~`
+    pub fn store_display(&self) -> String {
+        display_path(&self.config_dir)
+    }
+
+    fn read_configs(dir: &PathBuf) -> Result<Vec<String>> {
         let mut configs = Vec::new();
 
         if dir.exists() {
@@ -41,6 +274,10 @@ impl ConfigManager {
                 if path.is_file() {
                     if let Some(name) = path.file_name() {
                         if let Some(name_str) = name.to_str() {
+                            // Hidden files and .toml files are tmucks' own data
+                            if name_str.starts_with('.') || name_str.ends_with(".toml") {
+                                continue;
+                            }
                             configs.push(name_str.to_string());
                         }
                     }
@@ -52,69 +289,1229 @@ impl ConfigManager {
         Ok(configs)
     }
 
-    pub fn apply_config(&self, config_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Validates a saved config, see `check::check_file`. Templates are
+    /// checked as they would be applied.
+    pub fn check_config(&self, config_name: &str) -> Result<Vec<Issue>> {
+        let config_path = self.config_dir.join(config_name);
+
+        if !config_path.exists() {
+            return Err(Error::ConfigNotFound(config_name.to_string()));
+        }
+
+        match self.render_template(config_name)? {
+            Some(rendered) => check_text(&rendered, &self.tmux),
+            None => check_file(&config_path, &self.tmux),
+        }
+    }
+
+    /// The values template variables resolve to. Built-in variables are
+    /// overridden by the variables file, then by `TMUCKS_VAR_*` environment
+    /// variables, then by `variables`.
+    pub fn resolve_variables(&self) -> Result<Variables> {
+        let mut variables = template::builtin();
+        variables.extend(template::load(&self.variables_path)?);
+        variables.extend(template::from_env());
+        variables.extend(self.variables.clone());
+        Ok(variables)
+    }
+
+    /// The variables file, with the home directory shortened to `~`
+    pub fn variables_display(&self) -> String {
+        display_path(&self.variables_path)
+    }
+
+    /// A saved config as it would be applied, with its template variables
+    /// filled in
+    pub fn render_config(&self, config_name: &str) -> Result<String> {
+        match self.render_template(config_name)? {
+            Some(rendered) => Ok(rendered),
+            None => self.read_config(config_name),
+        }
+    }
+
+    /// Fills in the template variables in config text that isn't saved in
+    /// the store, like a `.tmucks` file. `name` is used in errors.
+    pub fn render_text(&self, name: &str, text: &str) -> Result<String> {
+        if template::placeholders(text).is_empty() {
+            return Ok(text.to_string());
+        }
+
+        template::render(text, &self.resolve_variables()?).map_err(|missing| {
+            Error::MissingVariables {
+                name: name.to_string(),
+                missing,
+            }
+        })
+    }
+
+    /// Renders a saved config that uses template variables, or returns None
+    /// if it doesn't use any
+    fn render_template(&self, config_name: &str) -> Result<Option<String>> {
+        let Ok(contents) = fs::read_to_string(self.config_dir.join(config_name)) else {
+            return Ok(None);
+        };
+        if template::placeholders(&contents).is_empty() {
+            return Ok(None);
+        }
+
+        self.render_text(config_name, &contents).map(Some)
+    }
+
+    /// The tmux session called `name`, or the one tmucks is running inside,
+    /// for setting options on it alone
+    pub fn session(&self, name: Option<&str>) -> Result<Session> {
+        Session::find(&self.tmux, name)
+    }
+
+    /// Applies a saved config. It is checked first and refused if it has
+    /// errors, unless `force` is set. Templates are always rendered and
+    /// written out, since a symlink would leave their placeholders in. A
+    /// tmux config that is a symlink out of the store, like into a dotfiles
+    /// repo, is written through; symlink mode only replaces it when `force`
+    /// is set.
+    pub fn apply_config(&self, config_name: &str, force: bool) -> Result<Reloads> {
         let source_path = self.config_dir.join(config_name);
 
         if !source_path.exists() {
-            return Err(format!("Config file not found: {}", config_name).into());
+            return Err(Error::ConfigNotFound(config_name.to_string()));
+        }
+
+        // Missing variables stop the apply before anything is touched
+        let rendered = self.render_template(config_name)?;
+
+        if !force {
+            if let (ApplyMode::Symlink, None, Some(link)) =
+                (self.apply_mode, &rendered, self.foreign_link())
+            {
+                return Err(Error::TargetIsLink {
+                    path: self.tmux_config_path.clone(),
+                    link,
+                });
+            }
+
+            let issues = self.check_config(config_name)?;
+            if !issues.is_empty() {
+                return Err(Error::ConfigErrors {
+                    name: config_name.to_string(),
+                    issues,
+                });
+            }
+        }
+
+        // Snapshot the current tmux config before overwriting it
+        let undo_entry = self.back_up_for_undo()?;
+
+        // XDG locations may not have their tmux/ directory yet
+        self.ensure_target_dir()?;
+        self.remove_store_link()?;
+
+        match (self.apply_mode, rendered) {
+            (_, Some(rendered)) => {
+                fs::write(&self.tmux_config_path, rendered)?;
+            }
+            (ApplyMode::Copy, None) => {
+                // Use cp command to copy the config
+                fs::copy(&source_path, &self.tmux_config_path)?;
+            }
+            (ApplyMode::Symlink, None) => {
+                // The regular file or link (if any) was backed up above
+                if fs::symlink_metadata(&self.tmux_config_path).is_ok() {
+                    fs::remove_file(&self.tmux_config_path)?;
+                }
+                symlink(source_path.canonicalize()?, &self.tmux_config_path)?;
+            }
+        }
+        self.push_undo(undo_entry)?;
+        self.write_active(Some(config_name))?;
+
+        Ok(self.reload_tmux())
+    }
+
+    /// The first of `rules` matching this machine, with its position, for
+    /// `tmucks auto` to apply
+    pub fn auto_rule(&self) -> Result<(usize, &Rule)> {
+        rules::select(&self.rules, &Environment::current()).ok_or(Error::NoRuleMatched)
+    }
+
+    /// Puts back the tmux config from `steps` applies ago and reloads tmux:
+    /// its contents, and the symlink if it was one. Contents are written
+    /// through a restored link unless it points at a profile in the store.
+    pub fn undo(&self, steps: usize) -> Result<Undone> {
+        let mut stack = self.read_undo_stack()?;
+
+        if steps == 0 {
+            return Err(Error::NoUndoSteps);
+        }
+        if stack.is_empty() {
+            return Err(Error::NothingToUndo);
+        }
+        if stack.len() < steps {
+            return Err(Error::UndoTooFar {
+                steps,
+                available: stack.len(),
+            });
+        }
+
+        let entry = stack.split_off(stack.len() - steps).remove(0);
+
+        // Read the target state first so the safety backup below can't prune it
+        let contents = match &entry.backup {
+            Some(backup) => {
+                let backup_path = self.backup_dir.join(backup);
+                if !backup_path.exists() {
+                    return Err(Error::BackupPruned(backup.clone()));
+                }
+                Some(fs::read(backup_path)?)
+            }
+            None => None,
+        };
+
+        // Keep what's there now recoverable from the backups list
+        self.backup_current_config()?;
+
+        match (&entry.link, &contents) {
+            (Some(link), _) => {
+                if fs::symlink_metadata(&self.tmux_config_path).is_ok() {
+                    fs::remove_file(&self.tmux_config_path)?;
+                }
+                self.ensure_target_dir()?;
+                symlink(link, &self.tmux_config_path)?;
+                if let (Some(contents), Some(_)) = (&contents, self.foreign_link()) {
+                    fs::write(&self.tmux_config_path, contents)?;
+                }
+            }
+            (None, Some(contents)) => {
+                self.remove_store_link()?;
+                self.ensure_target_dir()?;
+                fs::write(&self.tmux_config_path, contents)?;
+            }
+            (None, None) => {
+                if fs::symlink_metadata(&self.tmux_config_path).is_ok() {
+                    fs::remove_file(&self.tmux_config_path)?;
+                }
+            }
+        }
+        self.write_undo_stack(&stack)?;
+        self.write_active(None)?;
+
+        let restored = entry.backup.is_some() || entry.link.is_some();
+        Ok(Undone {
+            backup: entry.backup,
+            link: entry.link,
+            reloads: restored.then(|| self.reload_tmux()),
+        })
+    }
+
+    /// Backs up the tmux config before it is overwritten, noting where it
+    /// links to so undo can put back a symlink as well as its contents
+    fn back_up_for_undo(&self) -> Result<UndoEntry> {
+        Ok(UndoEntry {
+            link: fs::read_link(&self.tmux_config_path).ok(),
+            backup: self.backup_current_config()?,
+        })
+    }
+
+    fn push_undo(&self, entry: UndoEntry) -> Result<()> {
+        let mut stack = self.read_undo_stack()?;
+        stack.push(entry);
+
+        // Entries older than the backup limit point at pruned backups anyway
+        if stack.len() > self.backup_limit {
+            stack.drain(..stack.len() - self.backup_limit);
+        }
+
+        self.write_undo_stack(&stack)
+    }
+
+    fn read_undo_stack(&self) -> Result<Vec<UndoEntry>> {
+        if !self.undo_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(fs::read_to_string(&self.undo_path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| UndoEntry::parse(line.trim()))
+            .collect())
+    }
+
+    fn write_undo_stack(&self, stack: &[UndoEntry]) -> Result<()> {
+        if let Some(parent) = self.undo_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut contents: String = stack
+            .iter()
+            .map(UndoEntry::to_line)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        fs::write(&self.undo_path, contents)?;
+        Ok(())
+    }
+
+    /// Works out which saved config the tmux config came from. A symlink into
+    /// the store wins, then a saved config with identical contents, then the
+    /// config recorded on the last apply (which is then marked as modified).
+    pub fn active_profile(&self) -> Option<ActiveProfile> {
+        if let Some(name) = self.linked_profile() {
+            return Some(ActiveProfile {
+                name,
+                modified: false,
+            });
+        }
+
+        let current = fs::read(&self.tmux_config_path).ok()?;
+        let recorded = fs::read_to_string(&self.active_path)
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| self.configs.contains(name));
+        // Templates are compared as they would have been applied
+        let matches = |name: &String| match self.render_template(name) {
+            Ok(Some(rendered)) => rendered.as_bytes() == current,
+            Ok(None) => fs::read(self.config_dir.join(name))
+                .map(|contents| contents == current)
+                .unwrap_or(false),
+            Err(_) => false,
+        };
+
+        if let Some(name) = recorded.as_ref().filter(|name| matches(name)) {
+            return Some(ActiveProfile {
+                name: name.clone(),
+                modified: false,
+            });
+        }
+
+        if let Some(name) = self.configs.iter().find(|name| matches(name)) {
+            return Some(ActiveProfile {
+                name: name.clone(),
+                modified: false,
+            });
+        }
+
+        recorded.map(|name| ActiveProfile {
+            name,
+            modified: true,
+        })
+    }
+
+    fn write_active(&self, config_name: Option<&str>) -> Result<()> {
+        match config_name {
+            Some(name) => {
+                if let Some(parent) = self.active_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&self.active_path, format!("{}\n", name))?;
+            }
+            None => {
+                if self.active_path.exists() {
+                    fs::remove_file(&self.active_path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the profile the tmux config is symlinked to, if it is a link
+    /// into the store
+    pub fn linked_profile(&self) -> Option<String> {
+        let link = self.target_link()?;
+        if !self.is_in_store(&link) {
+            return None;
+        }
+
+        let name = link.file_name()?.to_str()?.to_string();
+        self.configs.contains(&name).then_some(name)
+    }
+
+    /// Where the tmux config links to when it is a symlink that tmucks
+    /// didn't make, like one into a dotfiles repo
+    pub fn foreign_link(&self) -> Option<PathBuf> {
+        self.target_link().filter(|link| !self.is_in_store(link))
+    }
+
+    /// Where the tmux config points if it is a symlink, relative links
+    /// resolved against its directory
+    fn target_link(&self) -> Option<PathBuf> {
+        let link = fs::read_link(&self.tmux_config_path).ok()?;
+        if link.is_relative() {
+            Some(self.tmux_config_path.parent()?.join(link))
+        } else {
+            Some(link)
         }
+    }
 
-        // Use cp command to copy the config
-        fs::copy(&source_path, &self.tmux_config_path)?;
+    fn is_in_store(&self, path: &Path) -> bool {
+        let store = self.config_dir.canonicalize().ok();
+        let parent = path.parent().and_then(|parent| parent.canonicalize().ok());
+        store.is_some() && store == parent
+    }
 
-        // Reload tmux config if tmux is running
-        if let Some(path_str) = self.tmux_config_path.to_str() {
-            let _ = std::process::Command::new("tmux")
-                .args(["source-file", path_str])
-                .output();
+    /// Removes the tmux config if it is a symlink into the store, so that
+    /// writing to it can't clobber the profile it points at. Other links
+    /// are left for writes to go through.
+    fn remove_store_link(&self) -> Result<()> {
+        if self
+            .target_link()
+            .is_some_and(|link| self.is_in_store(&link))
+        {
+            fs::remove_file(&self.tmux_config_path)?;
         }
+        Ok(())
+    }
 
+    fn ensure_target_dir(&self) -> Result<()> {
+        if let Some(parent) = self.tmux_config_path.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                fs::create_dir_all(parent)?;
+            }
+        }
         Ok(())
     }
 
-    pub fn delete_config(&self, config_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Sources the tmux config into each server in `reload_targets`
+    fn reload_tmux(&self) -> Reloads {
+        self.tmux
+            .reload_all(&self.tmux_config_path, &self.reload_targets.servers())
+    }
+
+    /// Copies the current tmux config into the backups directory and prunes
+    /// old snapshots. Returns the backup name, or None if there was nothing to
+    /// back up.
+    pub fn backup_current_config(&self) -> Result<Option<String>> {
+        if !self.tmux_config_path.exists() {
+            return Ok(None);
+        }
+
+        if !self.backup_dir.exists() {
+            fs::create_dir_all(&self.backup_dir)?;
+        }
+
+        // Timestamped names sort chronologically; add a counter if two
+        // backups land in the same second
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut backup_name = format!("{}.conf", timestamp);
+        let mut counter = 1;
+        while self.backup_dir.join(&backup_name).exists() {
+            backup_name = format!("{}_{}.conf", timestamp, counter);
+            counter += 1;
+        }
+
+        fs::copy(&self.tmux_config_path, self.backup_dir.join(&backup_name))?;
+        self.prune_backups(self.backup_limit)?;
+
+        Ok(Some(backup_name))
+    }
+
+    /// Lists backups, newest first
+    pub fn list_backups(&self) -> Result<Vec<String>> {
+        let mut backups = Self::read_configs(&self.backup_dir)?;
+        backups.reverse();
+        Ok(backups)
+    }
+
+    /// Path of a saved config in the store, for handing to an editor
+    pub fn config_path(&self, config_name: &str) -> Result<PathBuf> {
+        let config_path = self.config_dir.join(config_name);
+
+        if !config_path.exists() {
+            return Err(Error::ConfigNotFound(config_name.to_string()));
+        }
+
+        Ok(config_path)
+    }
+
+    /// The contents of a saved config as stored, without its template
+    /// variables filled in. `render_config` fills them in.
+    pub fn read_config(&self, config_name: &str) -> Result<String> {
+        let config_path = self.config_dir.join(config_name);
+
+        if !config_path.exists() {
+            return Err(Error::ConfigNotFound(config_name.to_string()));
+        }
+
+        Ok(fs::read_to_string(config_path)?)
+    }
+
+    /// Reads the live tmux config
+    pub fn read_target(&self) -> Result<String> {
+        if !self.tmux_config_path.exists() {
+            return Err(Error::NoTmuxConfig(self.tmux_config_path.clone()));
+        }
+
+        Ok(fs::read_to_string(&self.tmux_config_path)?)
+    }
+
+    /// The contents of a backup of the tmux config
+    pub fn read_backup(&self, backup_name: &str) -> Result<String> {
+        let backup_path = self.backup_dir.join(backup_name);
+
+        if !backup_path.exists() {
+            return Err(Error::BackupNotFound(backup_name.to_string()));
+        }
+
+        Ok(fs::read_to_string(backup_path)?)
+    }
+
+    /// Puts a backup back at the tmux config path and reloads tmux. What
+    /// was there is backed up first, and the restore can be undone like an
+    /// apply.
+    pub fn restore_backup(&self, backup_name: &str) -> Result<Reloads> {
+        let backup_path = self.backup_dir.join(backup_name);
+
+        if !backup_path.exists() {
+            return Err(Error::BackupNotFound(backup_name.to_string()));
+        }
+
+        // Read first so pruning during the safety backup can't remove it
+        let contents = fs::read(&backup_path)?;
+
+        // Restoring is itself an overwrite, so snapshot what's there now
+        let undo_entry = self.back_up_for_undo()?;
+
+        self.ensure_target_dir()?;
+        self.remove_store_link()?;
+        fs::write(&self.tmux_config_path, contents)?;
+        self.push_undo(undo_entry)?;
+        self.write_active(None)?;
+
+        Ok(self.reload_tmux())
+    }
+
+    /// Removes the oldest backups so that at most `keep` remain. Returns the
+    /// number of backups removed.
+    pub fn prune_backups(&self, keep: usize) -> Result<usize> {
+        let backups = self.list_backups()?;
+        let mut removed = 0;
+
+        for backup_name in backups.iter().skip(keep) {
+            fs::remove_file(self.backup_dir.join(backup_name))?;
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
+    /// Moves a saved config to the trash, where `restore_from_trash` can get
+    /// it back
+    pub fn delete_config(&self, config_name: &str) -> Result<()> {
         let config_path = self.config_dir.join(config_name);
 
         if !config_path.exists() {
-            return Err(format!("Config file not found: {}", config_name).into());
+            return Err(Error::ConfigNotFound(config_name.to_string()));
+        }
+
+        // A tmux config symlinked to this one would be left dangling, so
+        // turn it into a copy first
+        if self.linked_profile().as_deref() == Some(config_name) {
+            self.remove_store_link()?;
+            fs::copy(&config_path, &self.tmux_config_path)?;
+        }
+
+        // Same naming as backups, so entries sort by deletion time
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut entry_dir = self.trash_dir.join(&timestamp);
+        let mut counter = 1;
+        while entry_dir.exists() {
+            entry_dir = self.trash_dir.join(format!("{}_{}", timestamp, counter));
+            counter += 1;
+        }
+        fs::create_dir_all(&entry_dir)?;
+
+        let trashed_path = entry_dir.join(config_name);
+        fs::rename(&config_path, &trashed_path)?;
+        Self::move_meta(&config_path, &trashed_path)?;
+        Ok(())
+    }
+
+    /// Lists deleted configs, most recently deleted first
+    pub fn list_trash(&self) -> Result<Vec<TrashEntry>> {
+        let mut entries = Vec::new();
+        if !self.trash_dir.exists() {
+            return Ok(entries);
+        }
+
+        let mut ids: Vec<String> = fs::read_dir(&self.trash_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(String::from))
+            .collect();
+        ids.sort();
+
+        for id in ids.into_iter().rev() {
+            for name in Self::read_configs(&self.trash_dir.join(&id))? {
+                entries.push(TrashEntry {
+                    id: id.clone(),
+                    name,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Puts the most recently deleted config called `config_name` back in
+    /// the store. An existing config with that name is only replaced when
+    /// `force` is set.
+    pub fn restore_from_trash(&self, config_name: &str, force: bool) -> Result<()> {
+        let entry = self
+            .list_trash()?
+            .into_iter()
+            .find(|entry| entry.name == config_name)
+            .ok_or_else(|| Error::NotInTrash(config_name.to_string()))?;
+
+        let dest_path = self.config_dir.join(config_name);
+        if dest_path.exists() && !force {
+            return Err(Error::ConfigExists(config_name.to_string()));
+        }
+
+        let entry_dir = self.trash_dir.join(&entry.id);
+        let trashed_path = entry_dir.join(config_name);
+        fs::rename(&trashed_path, &dest_path)?;
+        Self::move_meta(&trashed_path, &dest_path)?;
+        // Only succeeds once nothing else is left in the entry
+        let _ = fs::remove_dir(entry_dir);
+        Ok(())
+    }
+
+    /// Permanently removes everything in the trash. Returns the number of
+    /// configs removed.
+    pub fn empty_trash(&self) -> Result<usize> {
+        let removed = self.list_trash()?.len();
+        if self.trash_dir.exists() {
+            fs::remove_dir_all(&self.trash_dir)?;
+        }
+        Ok(removed)
+    }
+
+    /// Renames a saved config. The active profile record and a tmux config
+    /// symlinked to it follow the new name. An existing config with the new
+    /// name is only replaced when `force` is set.
+    pub fn rename_config(&self, old_name: &str, new_name: &str, force: bool) -> Result<()> {
+        let old_path = self.prepare_copy(old_name, new_name, force)?;
+        let new_path = self.config_dir.join(new_name);
+
+        // Work these out before the old name disappears from the store
+        let linked = self.linked_profile().as_deref() == Some(old_name);
+        let recorded = fs::read_to_string(&self.active_path)
+            .map(|name| name.trim() == old_name)
+            .unwrap_or(false);
+
+        fs::rename(&old_path, &new_path)?;
+        Self::move_meta(&old_path, &new_path)?;
+
+        if linked {
+            self.remove_store_link()?;
+            symlink(new_path.canonicalize()?, &self.tmux_config_path)?;
+        }
+        if recorded {
+            self.write_active(Some(new_name))?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies a saved config to a new name. An existing config with that name
+    /// is only replaced when `force` is set.
+    pub fn copy_config(&self, source_name: &str, dest_name: &str, force: bool) -> Result<()> {
+        let source_path = self.prepare_copy(source_name, dest_name, force)?;
+        let dest_path = self.config_dir.join(dest_name);
+        fs::copy(&source_path, &dest_path)?;
+
+        // The copy keeps the description and tags but starts its own history
+        let mut meta = ProfileMeta::load(&source_path)?;
+        if meta.is_empty() {
+            Self::remove_meta(&dest_path)?;
+        } else {
+            meta.touch();
+            meta.created = meta.updated.clone();
+            meta.save(&dest_path)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the metadata of a config that was moved from `from` to `to`.
+    /// Metadata left at `to` by a config that was overwritten is removed.
+    fn move_meta(from: &Path, to: &Path) -> Result<()> {
+        if meta_path(from).exists() {
+            fs::rename(meta_path(from), meta_path(to))?;
+        } else {
+            Self::remove_meta(to)?;
         }
+        Ok(())
+    }
 
-        fs::remove_file(config_path)?;
+    fn remove_meta(config_path: &Path) -> Result<()> {
+        if meta_path(config_path).exists() {
+            fs::remove_file(meta_path(config_path))?;
+        }
         Ok(())
     }
 
-    pub fn save_current_config(&self, config_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// The metadata recorded for a saved config
+    pub fn config_meta(&self, config_name: &str) -> Result<ProfileMeta> {
+        ProfileMeta::load(&self.config_dir.join(config_name))
+    }
+
+    /// Records that a saved config's contents changed just now
+    pub fn touch_config(&self, config_name: &str) -> Result<()> {
+        let config_path = self.config_dir.join(config_name);
+        let mut meta = ProfileMeta::load(&config_path)?;
+        meta.touch();
+        if let Some(version) = self.tmux.version() {
+            meta.tmux_version = Some(version);
+        }
+        meta.save(&config_path)
+    }
+
+    /// Checks that `source_name` can be renamed or copied to `dest_name`,
+    /// returning the source path
+    fn prepare_copy(&self, source_name: &str, dest_name: &str, force: bool) -> Result<PathBuf> {
+        let source_path = self.config_dir.join(source_name);
+
+        if !source_path.exists() {
+            return Err(Error::ConfigNotFound(source_name.to_string()));
+        }
+        if source_name == dest_name {
+            return Err(Error::SameName(source_name.to_string()));
+        }
+        if self.config_dir.join(dest_name).exists() && !force {
+            return Err(Error::ConfigExists(dest_name.to_string()));
+        }
+
+        Ok(source_path)
+    }
+
+    /// Saves a copy of the tmux config as a new config with the given
+    /// metadata. The tmux version is recorded unless `meta` has one.
+    pub fn save_current_config(&self, config_name: &str, meta: &ProfileMeta) -> Result<()> {
         if !self.tmux_config_path.exists() {
-            return Err("No tmux config file found at ~/.tmux.conf".into());
+            return Err(Error::NoTmuxConfig(self.tmux_config_path.clone()));
         }
 
         let dest_path = self.config_dir.join(config_name);
-        
+
         // Check if config already exists
         if dest_path.exists() {
-            return Err(format!("Config '{}' already exists. Use 'update' command to overwrite an existing config.", config_name).into());
+            return Err(Error::ConfigExists(config_name.to_string()));
         }
-        
+
         fs::copy(&self.tmux_config_path, &dest_path)?;
+        let mut meta = meta.clone();
+        if meta.tmux_version.is_none() {
+            meta.tmux_version = self.tmux.version();
+        }
+        meta.save(&dest_path)?;
+
+        Ok(())
+    }
+
+    /// Saves the live state of a running server as a new config, keeping
+    /// only the settings that differ from tmux's defaults
+    pub fn save_snapshot(
+        &self,
+        config_name: &str,
+        server: &Server,
+        meta: &ProfileMeta,
+    ) -> Result<()> {
+        let dest_path = self.config_dir.join(config_name);
+        if dest_path.exists() {
+            return Err(Error::ConfigExists(config_name.to_string()));
+        }
+
+        let config = snapshot::capture(&self.tmux, server)?;
+        fs::write(&dest_path, config)?;
+        let mut meta = meta.clone();
+        if meta.tmux_version.is_none() {
+            meta.tmux_version = self.tmux.version();
+        }
+        meta.save(&dest_path)?;
 
         Ok(())
     }
 
-    pub fn update_config(&self, config_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Overwrites a saved config with the tmux config and records the
+    /// change in its metadata. Nothing is copied when the tmux config is a
+    /// symlink to it, and configs using template variables are refused.
+    pub fn update_config(&self, config_name: &str) -> Result<()> {
         if !self.tmux_config_path.exists() {
-            return Err("No tmux config file found at ~/.tmux.conf".into());
+            return Err(Error::NoTmuxConfig(self.tmux_config_path.clone()));
         }
 
         let dest_path = self.config_dir.join(config_name);
-        
+
         // Check if config exists
         if !dest_path.exists() {
-            return Err(format!("Config '{}' does not exist. Use 'save' command to create a new config.", config_name).into());
+            return Err(Error::ConfigNotFound(config_name.to_string()));
+        }
+
+        // A symlinked target already is this config, and copying a file onto
+        // itself would truncate it
+        if self.linked_profile().as_deref() == Some(config_name) {
+            return Ok(());
+        }
+
+        // The tmux config has the template's variables filled in
+        if !template::placeholders(&fs::read_to_string(&dest_path).unwrap_or_default()).is_empty() {
+            return Err(Error::TemplateUpdate(config_name.to_string()));
         }
-        
-        // Copy current .tmux.conf to the selected config file (overwriting it)
+
+        // Copy the current tmux config to the selected config file (overwriting it)
         fs::copy(&self.tmux_config_path, &dest_path)?;
+        self.touch_config(config_name)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A store and tmux config in a scratch directory, with reloading off
+    fn manager(dir: &TempDir) -> ConfigManager {
+        let mut manager =
+            ConfigManager::open(dir.path().join("store"), dir.path().join("tmux.conf")).unwrap();
+        manager.reload_targets = ReloadTargets::Sockets(Vec::new());
+        manager
+    }
+
+    #[test]
+    fn detects_the_tmux_config_tmux_loads() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        let xdg = home.join("xdg");
+        let place = |path: &Path| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+            path.to_path_buf()
+        };
+        let detect = || ConfigManager::detect_tmux_config(home, Some(&xdg));
+
+        // None yet, so the classic location
+        assert_eq!(detect(), home.join(".tmux.conf"));
+
+        let dot_config = place(&home.join(".config/tmux/tmux.conf"));
+        assert_eq!(detect(), dot_config);
+
+        // $XDG_CONFIG_HOME goes before ~/.config, when it is set
+        let xdg_config = place(&xdg.join("tmux/tmux.conf"));
+        assert_eq!(detect(), xdg_config);
+        assert_eq!(ConfigManager::detect_tmux_config(home, None), dot_config);
+
+        // ~/.tmux.conf goes before both
+        let classic = place(&home.join(".tmux.conf"));
+        assert_eq!(detect(), classic);
+    }
+
+    #[test]
+    fn target_override_wins_over_settings_and_detection() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        fs::write(home.join(".tmux.conf"), "").unwrap();
+        let choose = |target_override: Option<&str>, settings_target: Option<&str>| {
+            ConfigManager::choose_target(
+                target_override.map(PathBuf::from),
+                settings_target,
+                home,
+                None,
+            )
+        };
+
+        assert_eq!(
+            choose(Some("/srv/tmux.conf"), Some("/etc/tmux.conf")),
+            PathBuf::from("/srv/tmux.conf")
+        );
+        assert_eq!(
+            choose(None, Some("/etc/tmux.conf")),
+            PathBuf::from("/etc/tmux.conf")
+        );
+        assert_eq!(choose(None, None), home.join(".tmux.conf"));
+    }
+
+    fn save(manager: &mut ConfigManager, name: &str, contents: &str) {
+        fs::write(manager.config_dir.join(name), contents).unwrap();
+        manager.refresh().unwrap();
+    }
+
+    /// A tmux config symlinked into a dotfiles directory, returning the file
+    /// it links to
+    fn dotfiles_link(dir: &TempDir, contents: &str) -> PathBuf {
+        let dotfiles = dir.path().join("dotfiles");
+        fs::create_dir(&dotfiles).unwrap();
+        let linked = dotfiles.join("tmux.conf");
+        fs::write(&linked, contents).unwrap();
+        symlink(&linked, dir.path().join("tmux.conf")).unwrap();
+        linked
+    }
+
+    #[test]
+    fn copy_apply_writes_through_foreign_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        save(&mut manager, "work.conf", "set -g mouse on\n");
+        let linked = dotfiles_link(&dir, "set -g mouse off\n");
+
+        manager.apply_config("work.conf", true).unwrap();
+
+        assert_eq!(fs::read_link(&manager.tmux_config_path).unwrap(), linked);
+        assert_eq!(fs::read_to_string(&linked).unwrap(), "set -g mouse on\n");
+        assert_eq!(manager.foreign_link(), Some(linked));
+    }
+
+    #[test]
+    fn symlink_apply_replaces_foreign_link_only_when_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        manager.apply_mode = ApplyMode::Symlink;
+        save(&mut manager, "work.conf", "set -g mouse on\n");
+        let linked = dotfiles_link(&dir, "set -g mouse off\n");
+
+        assert!(matches!(
+            manager.apply_config("work.conf", false),
+            Err(Error::TargetIsLink { link, .. }) if link == linked
+        ));
+        assert_eq!(fs::read_link(&manager.tmux_config_path).unwrap(), linked);
+
+        manager.apply_config("work.conf", true).unwrap();
+        assert_eq!(manager.linked_profile().as_deref(), Some("work.conf"));
+        assert_eq!(fs::read_to_string(&linked).unwrap(), "set -g mouse off\n");
+    }
+
+    fn target(manager: &ConfigManager) -> String {
+        fs::read_to_string(&manager.tmux_config_path).unwrap()
+    }
+
+    #[test]
+    fn undo_goes_back_several_applies() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        fs::write(&manager.tmux_config_path, "original\n").unwrap();
+        for name in ["a.conf", "b.conf", "c.conf"] {
+            save(&mut manager, name, name);
+            manager.apply_config(name, true).unwrap();
+        }
+
+        assert!(matches!(
+            manager.undo(4),
+            Err(Error::UndoTooFar {
+                steps: 4,
+                available: 3
+            })
+        ));
+        assert!(matches!(manager.undo(0), Err(Error::NoUndoSteps)));
+
+        let undone = manager.undo(2).unwrap();
+        assert!(undone.backup.is_some() && undone.reloads.is_some());
+        assert_eq!(target(&manager), "a.conf");
+        manager.undo(1).unwrap();
+        assert_eq!(target(&manager), "original\n");
+        assert!(matches!(manager.undo(1), Err(Error::NothingToUndo)));
+    }
+
+    #[test]
+    fn undo_removes_a_config_that_did_not_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        save(&mut manager, "a.conf", "a");
+        manager.apply_config("a.conf", true).unwrap();
+        assert_eq!(
+            fs::read_to_string(&manager.undo_path).unwrap(),
+            format!("{}\n", UNDO_NO_CONFIG)
+        );
+
+        let undone = manager.undo(1).unwrap();
+        assert!(undone.backup.is_none() && undone.reloads.is_none());
+        assert!(!manager.tmux_config_path.exists());
+    }
+
+    #[test]
+    fn undo_needs_its_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        fs::write(&manager.tmux_config_path, "original\n").unwrap();
+        save(&mut manager, "a.conf", "a");
+        manager.apply_config("a.conf", true).unwrap();
+        manager.prune_backups(0).unwrap();
+
+        assert!(matches!(manager.undo(1), Err(Error::BackupPruned(_))));
+        assert_eq!(target(&manager), "a");
+    }
+
+    #[test]
+    fn undo_puts_back_foreign_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        save(&mut manager, "work.conf", "set -g mouse on\n");
+        let linked = dotfiles_link(&dir, "set -g mouse off\n");
+
+        // Written through the link, then replaced by a link into the store
+        manager.apply_config("work.conf", true).unwrap();
+        manager.apply_mode = ApplyMode::Symlink;
+        manager.apply_config("work.conf", true).unwrap();
+        assert_eq!(manager.linked_profile().as_deref(), Some("work.conf"));
+
+        let undone = manager.undo(1).unwrap();
+        assert_eq!(undone.link.as_ref(), Some(&linked));
+        assert_eq!(fs::read_link(&manager.tmux_config_path).unwrap(), linked);
+        assert_eq!(fs::read_to_string(&linked).unwrap(), "set -g mouse on\n");
+
+        manager.undo(1).unwrap();
+        assert_eq!(fs::read_link(&manager.tmux_config_path).unwrap(), linked);
+        assert_eq!(fs::read_to_string(&linked).unwrap(), "set -g mouse off\n");
+        assert_eq!(
+            fs::read_to_string(manager.config_dir.join("work.conf")).unwrap(),
+            "set -g mouse on\n"
+        );
+    }
+
+    #[test]
+    fn undo_puts_back_store_links_without_writing_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        manager.apply_mode = ApplyMode::Symlink;
+        save(&mut manager, "a.conf", "a");
+        save(&mut manager, "b.conf", "b");
+        manager.apply_config("a.conf", true).unwrap();
+        manager.apply_config("b.conf", true).unwrap();
+
+        // a.conf changed after the backup taken when b.conf was applied
+        fs::write(manager.config_dir.join("a.conf"), "a, edited").unwrap();
+        manager.undo(1).unwrap();
+        assert_eq!(manager.linked_profile().as_deref(), Some("a.conf"));
+        assert_eq!(target(&manager), "a, edited");
+    }
+
+    #[test]
+    fn backups_are_named_by_time_and_listed_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+        assert_eq!(manager.backup_current_config().unwrap(), None);
+
+        fs::write(&manager.tmux_config_path, "set -g mouse on\n").unwrap();
+        let mut made = Vec::new();
+        for _ in 0..3 {
+            made.push(manager.backup_current_config().unwrap().unwrap());
+        }
+
+        // YYYYmmdd-HHMMSS.conf, with a counter for more in the same second
+        for name in &made {
+            let (stamp, rest) = name.split_at(15);
+            assert!(chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S").is_ok());
+            assert!(rest == ".conf" || (rest.starts_with('_') && rest.ends_with(".conf")));
+        }
+        made.reverse();
+        assert_eq!(manager.list_backups().unwrap(), made);
+        assert_eq!(manager.read_backup(&made[0]).unwrap(), "set -g mouse on\n");
+    }
+
+    #[test]
+    fn pruning_removes_the_oldest_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+        fs::create_dir_all(&manager.backup_dir).unwrap();
+        for name in [
+            "20240101-120000.conf",
+            "20240101-120000_1.conf",
+            "20240102-090000.conf",
+            "20231231-235959.conf",
+        ] {
+            fs::write(manager.backup_dir.join(name), name).unwrap();
+        }
+
+        assert_eq!(manager.prune_backups(2).unwrap(), 2);
+        assert_eq!(
+            manager.list_backups().unwrap(),
+            vec!["20240102-090000.conf", "20240101-120000_1.conf"]
+        );
+        assert_eq!(manager.prune_backups(2).unwrap(), 0);
+    }
+
+    #[test]
+    fn applying_keeps_backups_to_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        manager.backup_limit = 2;
+        save(&mut manager, "a.conf", "a");
+        save(&mut manager, "b.conf", "b");
+
+        for name in ["a.conf", "b.conf", "a.conf", "b.conf"] {
+            manager.apply_config(name, true).unwrap();
+        }
+
+        // The first apply had nothing to back up
+        assert_eq!(manager.list_backups().unwrap().len(), 2);
+        assert_eq!(manager.read_undo_stack().unwrap().len(), 2);
+        assert!(matches!(
+            manager.undo(3),
+            Err(Error::UndoTooFar {
+                steps: 3,
+                available: 2
+            })
+        ));
+        manager.undo(2).unwrap();
+        assert_eq!(target(&manager), "b");
+    }
+
+    #[test]
+    fn restoring_a_backup_can_be_undone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        save(&mut manager, "a.conf", "a");
+        save(&mut manager, "b.conf", "b");
+        manager.apply_config("a.conf", true).unwrap();
+        manager.apply_config("b.conf", true).unwrap();
+        let backup = manager.list_backups().unwrap()[0].clone();
+        assert_eq!(manager.read_backup(&backup).unwrap(), "a");
+
+        manager.restore_backup(&backup).unwrap();
+        assert_eq!(target(&manager), "a");
+        // A restored backup isn't an apply of any saved config
+        assert!(!manager.active_path.exists());
+
+        manager.undo(1).unwrap();
+        assert_eq!(target(&manager), "b");
+
+        assert!(matches!(
+            manager.restore_backup("19700101-000000.conf"),
+            Err(Error::BackupNotFound(_))
+        ));
+        assert_eq!(target(&manager), "b");
+    }
+
+    fn described(description: &str) -> ProfileMeta {
+        ProfileMeta {
+            description: Some(description.to_string()),
+            ..ProfileMeta::default()
+        }
+    }
+
+    #[test]
+    fn trash_restores_configs_with_their_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        save(&mut manager, "work.conf", "first");
+        described("first")
+            .save(&manager.config_dir.join("work.conf"))
+            .unwrap();
+        manager.delete_config("work.conf").unwrap();
+        manager.refresh().unwrap();
+        assert!(manager.configs.is_empty());
+
+        // A newer config with the same name, without metadata, is kept
+        // unless forced
+        save(&mut manager, "work.conf", "second");
+        let trash = manager.list_trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].name, "work.conf");
+        assert!(matches!(
+            manager.restore_from_trash("work.conf", false),
+            Err(Error::ConfigExists(_))
+        ));
+
+        manager.restore_from_trash("work.conf", true).unwrap();
+        assert_eq!(manager.read_config("work.conf").unwrap(), "first");
+        let meta = manager.config_meta("work.conf").unwrap();
+        assert_eq!(meta.description.as_deref(), Some("first"));
+        assert!(manager.list_trash().unwrap().is_empty());
+        assert!(matches!(
+            manager.restore_from_trash("work.conf", false),
+            Err(Error::NotInTrash(_))
+        ));
+    }
+
+    #[test]
+    fn deleting_the_linked_config_leaves_a_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        manager.apply_mode = ApplyMode::Symlink;
+        save(&mut manager, "work.conf", "work");
+        manager.apply_config("work.conf", true).unwrap();
+
+        manager.delete_config("work.conf").unwrap();
+        assert!(fs::read_link(&manager.tmux_config_path).is_err());
+        assert_eq!(target(&manager), "work");
+    }
+
+    #[test]
+    fn rename_moves_metadata_and_follows_the_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        manager.apply_mode = ApplyMode::Symlink;
+        save(&mut manager, "old.conf", "old");
+        described("old")
+            .save(&manager.config_dir.join("old.conf"))
+            .unwrap();
+        save(&mut manager, "new.conf", "replaced");
+        described("replaced")
+            .save(&manager.config_dir.join("new.conf"))
+            .unwrap();
+        manager.apply_config("old.conf", true).unwrap();
+
+        assert!(matches!(
+            manager.rename_config("old.conf", "new.conf", false),
+            Err(Error::ConfigExists(_))
+        ));
+        assert!(matches!(
+            manager.rename_config("old.conf", "old.conf", true),
+            Err(Error::SameName(_))
+        ));
+
+        manager.rename_config("old.conf", "new.conf", true).unwrap();
+        manager.refresh().unwrap();
+        assert_eq!(manager.configs, vec!["new.conf"]);
+        assert!(!meta_path(&manager.config_dir.join("old.conf")).exists());
+        let meta = manager.config_meta("new.conf").unwrap();
+        assert_eq!(meta.description.as_deref(), Some("old"));
+        assert_eq!(manager.linked_profile().as_deref(), Some("new.conf"));
+        assert_eq!(manager.active_profile().unwrap().name, "new.conf");
+    }
+
+    #[test]
+    fn rename_drops_metadata_of_the_replaced_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        save(&mut manager, "old.conf", "old");
+        save(&mut manager, "new.conf", "replaced");
+        described("replaced")
+            .save(&manager.config_dir.join("new.conf"))
+            .unwrap();
+
+        manager.rename_config("old.conf", "new.conf", true).unwrap();
+        assert!(manager.config_meta("new.conf").unwrap().is_empty());
+    }
+
+    #[test]
+    fn copy_keeps_description_and_starts_its_own_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = manager(&dir);
+        save(&mut manager, "base.conf", "base");
+        let mut meta = described("base");
+        meta.tags = vec![String::from("work")];
+        meta.created = Some(String::from("2020-01-01 00:00:00"));
+        meta.updated = meta.created.clone();
+        meta.save(&manager.config_dir.join("base.conf")).unwrap();
+
+        manager
+            .copy_config("base.conf", "copy.conf", false)
+            .unwrap();
+        assert_eq!(manager.read_config("copy.conf").unwrap(), "base");
+        let copied = manager.config_meta("copy.conf").unwrap();
+        assert_eq!(copied.description.as_deref(), Some("base"));
+        assert_eq!(copied.tags, vec!["work"]);
+        assert_ne!(copied.created, meta.created);
+        assert_eq!(copied.created, copied.updated);
+        // The source is left alone
+        let source = manager.config_meta("base.conf").unwrap();
+        assert_eq!(source.created, meta.created);
+
+        // Copying a config without metadata removes what the replaced one had
+        save(&mut manager, "plain.conf", "plain");
+        manager
+            .copy_config("plain.conf", "copy.conf", true)
+            .unwrap();
+        assert!(manager.config_meta("copy.conf").unwrap().is_empty());
+    }
+}
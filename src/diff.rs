@@ -0,0 +1,89 @@
+//! Line-by-line diffs between configs.
+
+use similar::{ChangeTag, TextDiff};
+
+/// What a line of a unified diff is, for colouring it
+#[derive(Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    /// The `---`/`+++` lines naming the two files
+    Header,
+    /// An `@@` line starting a hunk
+    Hunk,
+    /// A line both files have
+    Context,
+    /// A line only the new file has
+    Added,
+    /// A line only the old file has
+    Removed,
+}
+
+/// One line of a unified diff
+pub struct DiffLine {
+    /// What the line is
+    pub kind: DiffKind,
+    /// The line as printed. Lines from the files start with `+`, `-` or a
+    /// space.
+    pub text: String,
+}
+
+/// Builds a unified diff between two config files. Returns no lines when the
+/// contents are identical.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> Vec<DiffLine> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+
+    for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+        if lines.is_empty() {
+            lines.push(DiffLine {
+                kind: DiffKind::Header,
+                text: format!("--- {}", old_name),
+            });
+            lines.push(DiffLine {
+                kind: DiffKind::Header,
+                text: format!("+++ {}", new_name),
+            });
+        }
+
+        lines.push(DiffLine {
+            kind: DiffKind::Hunk,
+            text: hunk.header().to_string(),
+        });
+
+        for change in hunk.iter_changes() {
+            let (kind, sign) = match change.tag() {
+                ChangeTag::Equal => (DiffKind::Context, ' '),
+                ChangeTag::Delete => (DiffKind::Removed, '-'),
+                ChangeTag::Insert => (DiffKind::Added, '+'),
+            };
+            let value = change.value();
+            lines.push(DiffLine {
+                kind,
+                text: format!("{}{}", sign, value.strip_suffix('\n').unwrap_or(value)),
+            });
+            if change.missing_newline() {
+                lines.push(DiffLine {
+                    kind: DiffKind::Context,
+                    text: String::from("\\ No newline at end of file"),
+                });
+            }
+        }
+    }
+
+    lines
+}
+
+/// Formats a diff line for a terminal, using ANSI colours when `color` is set
+pub fn ansi_line(line: &DiffLine, color: bool) -> String {
+    if !color {
+        return line.text.clone();
+    }
+
+    let code = match line.kind {
+        DiffKind::Header => "1",
+        DiffKind::Hunk => "36",
+        DiffKind::Context => return line.text.clone(),
+        DiffKind::Added => "32",
+        DiffKind::Removed => "31",
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, line.text)
+}
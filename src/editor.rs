@@ -0,0 +1,33 @@
+use std::{env, path::Path, process::Command};
+
+use tmucks::error::{Error, Result};
+
+/// Opens `path` in the user's editor, `$VISUAL` or `$EDITOR`, falling back
+/// to vi. Blocks until the editor exits.
+pub fn edit(path: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| String::from("vi"));
+
+    // Editors are often set with arguments, like `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| Error::Editor {
+            editor: editor.clone(),
+            message: format!("could not be run: {}", e),
+        })?;
+
+    if !status.success() {
+        return Err(Error::Editor {
+            editor,
+            message: format!("exited with {}", status),
+        });
+    }
+    Ok(())
+}
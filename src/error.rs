@@ -0,0 +1,170 @@
+//! The error type shared by everything in the crate.
+
+use std::{fmt, io, path::PathBuf};
+
+use crate::check::Issue;
+use crate::settings::display_path;
+
+/// Result of everything in the crate that can fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong while managing configs
+#[derive(Debug)]
+pub enum Error {
+    /// The home directory, which holds the store and settings, is unknown
+    NoHome,
+    /// There is no saved config with this name
+    ConfigNotFound(String),
+    /// A saved config with this name already exists
+    ConfigExists(String),
+    /// The tmux config is a symlink out of the store, which a symlink mode
+    /// apply would replace
+    TargetIsLink {
+        /// The tmux config path
+        path: PathBuf,
+        /// Where the symlink points
+        link: PathBuf,
+    },
+    /// A config was to be renamed or copied onto its own name
+    SameName(String),
+    /// There is no tmux config at the target path to save or compare
+    NoTmuxConfig(PathBuf),
+    /// There is no backup with this name
+    BackupNotFound(String),
+    /// An undo needed this backup, but it has been pruned
+    BackupPruned(String),
+    /// There is no config with this name in the trash
+    NotInTrash(String),
+    /// A config was checked before applying and has problems
+    ConfigErrors {
+        /// The config's name
+        name: String,
+        /// Everything the check found
+        issues: Vec<Issue>,
+    },
+    /// A config uses template variables that have no value
+    MissingVariables {
+        /// The config's name
+        name: String,
+        /// Names of the variables without a value
+        missing: Vec<String>,
+    },
+    /// A config uses template variables, so overwriting it with the rendered
+    /// tmux config would lose them
+    TemplateUpdate(String),
+    /// No applies have been recorded since the last undo
+    NothingToUndo,
+    /// None of the settings file's rules match this machine
+    NoRuleMatched,
+    /// Fewer applies were recorded than the undo asked to go back
+    UndoTooFar {
+        /// How many applies the undo asked to go back
+        steps: usize,
+        /// How many are recorded
+        available: usize,
+    },
+    /// An undo was asked to go back zero steps
+    NoUndoSteps,
+    /// A TOML file tmucks keeps, like the settings or a config's metadata,
+    /// could not be read or written
+    InvalidFile {
+        /// The file
+        path: PathBuf,
+        /// What is wrong with it
+        message: String,
+    },
+    /// The user's editor could not be run or failed
+    Editor {
+        /// The editor command, from `$VISUAL` or `$EDITOR`
+        editor: String,
+        /// How it went wrong
+        message: String,
+    },
+    /// There is no tmux session with this name, or tmucks isn't running
+    /// inside one when no name was given
+    NoSession(Option<String>),
+    /// tmux could not be run or refused a command, with its message
+    Tmux(String),
+    /// Reading or writing a file failed
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoHome => write!(f, "Could not find home directory"),
+            Error::ConfigNotFound(name) => write!(f, "Config file not found: {}", name),
+            Error::ConfigExists(name) => write!(f, "Config '{}' already exists", name),
+            Error::TargetIsLink { path, link } => write!(
+                f,
+                "{} is a symlink to {}, not replacing it (use --force to replace it)",
+                display_path(path),
+                display_path(link)
+            ),
+            Error::SameName(name) => write!(f, "'{}' is already called that", name),
+            Error::NoTmuxConfig(path) => {
+                write!(f, "No tmux config file found at {}", display_path(path))
+            }
+            Error::BackupNotFound(name) => write!(f, "Backup not found: {}", name),
+            Error::BackupPruned(name) => {
+                write!(f, "Backup '{}' needed for undo has been pruned", name)
+            }
+            Error::NotInTrash(name) => write!(f, "Config '{}' is not in the trash", name),
+            Error::ConfigErrors { name, issues } => {
+                write!(
+                    f,
+                    "Config '{}' has errors, not applying (use --force to apply anyway):",
+                    name
+                )?;
+                for issue in issues {
+                    write!(f, "\n  {}", issue)?;
+                }
+                Ok(())
+            }
+            Error::MissingVariables { name, missing } => write!(
+                f,
+                "Config '{}' uses variables with no value: {}",
+                name,
+                missing.join(", ")
+            ),
+            Error::TemplateUpdate(name) => write!(
+                f,
+                "Config '{}' uses template variables, updating it would replace them with their values",
+                name
+            ),
+            Error::NothingToUndo => write!(f, "Nothing to undo"),
+            Error::NoRuleMatched => write!(f, "No rule in the settings file matches this machine"),
+            Error::UndoTooFar { steps, available } => write!(
+                f,
+                "Only {} apply(s) can be undone, cannot go back {}",
+                available, steps
+            ),
+            Error::NoUndoSteps => write!(f, "Number of undo steps must be at least 1"),
+            Error::InvalidFile { path, message } => {
+                write!(f, "Invalid file {}: {}", display_path(path), message)
+            }
+            Error::Editor { editor, message } => write!(f, "Editor '{}' {}", editor, message),
+            Error::NoSession(Some(name)) => write!(f, "No tmux session named '{}'", name),
+            Error::NoSession(None) => {
+                write!(f, "Not inside a tmux session (use --session to name one)")
+            }
+            Error::Tmux(message) => write!(f, "{}", message),
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
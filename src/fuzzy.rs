@@ -0,0 +1,73 @@
+/// Where and how well a search query matched a piece of text
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    /// Higher is better
+    pub score: i32,
+    /// Char indices into the text of the matched characters, in order
+    pub positions: Vec<usize>,
+}
+
+/// Matches `query` against `text` as an in-order, case-insensitive
+/// subsequence. Runs of consecutive characters and matches at the start of a
+/// word score higher. An empty query matches everything with a score of 0.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<Match> {
+    let text: Vec<char> = text.chars().collect();
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut start = 0;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let offset = text[start..]
+            .iter()
+            .position(|c| c.to_lowercase().eq(wanted.to_lowercase()))?;
+        let i = start + offset;
+
+        score += 1;
+        if positions.last().is_some_and(|&last| last + 1 == i) {
+            score += 5;
+        }
+        if i == 0 || !text[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        // Skipping ahead before the first match costs nothing
+        if !positions.is_empty() {
+            score -= offset as i32;
+        }
+
+        positions.push(i);
+        start = i + 1;
+    }
+
+    Some(Match { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_query_matches_everything() {
+        let found = fuzzy_match("", "work.conf").unwrap();
+        assert_eq!(found.score, 0);
+        assert!(found.positions.is_empty());
+    }
+
+    #[test]
+    fn matches_subsequence_case_insensitively() {
+        let found = fuzzy_match("WkC", "work.conf").unwrap();
+        assert_eq!(found.positions, vec![0, 3, 5]);
+        assert!(fuzzy_match("wz", "work.conf").is_none());
+        assert!(fuzzy_match("kw", "work.conf").is_none());
+    }
+
+    #[test]
+    fn prefers_consecutive_and_word_start_matches() {
+        let prefix = fuzzy_match("dev", "dev-laptop.conf").unwrap();
+        let scattered = fuzzy_match("dev", "dark-evening.conf").unwrap();
+        assert!(prefix.score > scattered.score);
+
+        let word_start = fuzzy_match("l", "dev-laptop.conf").unwrap();
+        let inside = fuzzy_match("l", "mild.conf").unwrap();
+        assert!(word_start.score > inside.score);
+    }
+}
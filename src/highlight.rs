@@ -0,0 +1,171 @@
+//! Syntax highlighting for tmux configs shown in the TUI.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use tmucks::parser;
+
+const SET_COMMANDS: &[&str] = &[
+    "set",
+    "set-option",
+    "setw",
+    "set-window-option",
+    "set-hook",
+    "set-environment",
+    "setenv",
+];
+
+const BIND_COMMANDS: &[&str] = &["bind", "bind-key", "unbind", "unbind-key"];
+
+/// Where a word sits within a tmux command
+#[derive(PartialEq)]
+enum Position {
+    Command,
+    FirstArgument,
+    Argument,
+}
+
+/// Colours a tmux config for display: comments, set and bind commands,
+/// flags, option names and quoted strings each get their own style
+pub fn highlight_conf(contents: &str) -> Vec<Line<'static>> {
+    contents.lines().map(highlight_line).collect()
+}
+
+fn highlight_line(line: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    let mut position = Position::Command;
+    let mut command = String::new();
+    let mut flag_value_pending = false;
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !c.is_whitespace() {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            spans.push(Span::raw(line[start..end].to_string()));
+            continue;
+        }
+
+        // A word starting with # comments out the rest of the line, unless
+        // it is a #{} format
+        if parser::starts_comment(c, line[start + c.len_utf8()..].chars().next()) {
+            spans.push(Span::styled(
+                line[start..].to_string(),
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            ));
+            break;
+        }
+
+        // Read one word, keeping quoted sections and escapes intact
+        let mut end = start;
+        let mut quote = None;
+        let mut escaped = false;
+        while let Some(&(i, c)) = chars.peek() {
+            if quote.is_none() && !escaped && c.is_whitespace() {
+                break;
+            }
+            match (quote, c) {
+                _ if escaped => escaped = false,
+                (_, '\\') if quote != Some('\'') => escaped = true,
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (Some(q), c) if c == q => quote = None,
+                _ => {}
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let word = &line[start..end];
+
+        let style = if word == "\\;" || word == ";" {
+            // Command separator, the next word starts a new command
+            position = Position::Command;
+            spans.push(Span::styled(
+                word.to_string(),
+                Style::default().fg(Color::DarkGray),
+            ));
+            continue;
+        } else if position == Position::Command {
+            command = word.to_string();
+            position = Position::FirstArgument;
+            if SET_COMMANDS.contains(&word) {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else if BIND_COMMANDS.contains(&word) {
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD)
+            }
+        } else if flag_value_pending {
+            flag_value_pending = false;
+            Style::default().fg(Color::Cyan)
+        } else if word.starts_with('-') && word.len() > 1 && position == Position::FirstArgument {
+            // Flags like -t and -T take the next word as their value
+            flag_value_pending = matches!(word, "-t" | "-T" | "-N");
+            Style::default().fg(Color::Cyan)
+        } else if word.starts_with('"') || word.starts_with('\'') {
+            Style::default().fg(Color::LightRed)
+        } else if position == Position::FirstArgument
+            && (SET_COMMANDS.contains(&command.as_str())
+                || BIND_COMMANDS.contains(&command.as_str()))
+        {
+            // The option name for set commands, the key for bind commands.
+            // Whatever follows a bound key is itself a command.
+            position = if command.starts_with("bind") {
+                Position::Command
+            } else {
+                Position::Argument
+            };
+            Style::default().fg(Color::Green)
+        } else {
+            position = Position::Argument;
+            Style::default().fg(Color::White)
+        };
+
+        spans.push(Span::styled(word.to_string(), style));
+    }
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<(String, Style)> {
+        highlight_line(line)
+            .spans
+            .into_iter()
+            .filter(|span| !span.content.trim().is_empty())
+            .map(|span| (span.content.into_owned(), span.style))
+            .collect()
+    }
+
+    #[test]
+    fn formats_are_not_comments() {
+        let comment = Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC);
+
+        let line = words("set -g status-right #{pane_title} # the title");
+        assert_eq!(line[3].0, "#{pane_title}");
+        assert_ne!(line[3].1, comment);
+        assert_eq!(line[4], (String::from("# the title"), comment));
+
+        assert_eq!(words("#set -g mouse on")[0].1, comment);
+    }
+}
@@ -0,0 +1,48 @@
+//! Saving, switching and checking tmux configs.
+//!
+//! A [`ConfigManager`] keeps named configs in a store directory and puts
+//! one of them in place at the tmux config path, backing up what was there
+//! and reloading the running tmux servers. The `tmucks` binary is a command
+//! line and TUI front end for it.
+//!
+//! ```no_run
+//! use tmucks::{ConfigManager, Tmux};
+//!
+//! # fn main() -> tmucks::Result<()> {
+//! let mut config_manager = ConfigManager::open(
+//!     "/home/me/.config/tmucks".into(),
+//!     "/home/me/.tmux.conf".into(),
+//! )?;
+//! config_manager.tmux = Tmux::new("/usr/local/bin/tmux");
+//!
+//! for (server, reload) in config_manager.apply_config("work.conf", false)? {
+//!     println!("{}: {:?}", server, reload);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod check;
+pub mod config;
+pub mod diff;
+pub mod error;
+pub mod meta;
+pub mod parser;
+pub mod project;
+pub mod rules;
+pub mod session;
+pub mod settings;
+pub mod snapshot;
+pub mod template;
+pub mod tmux;
+
+pub use config::{ActiveProfile, ApplyMode, ConfigManager, TrashEntry, Undone};
+pub use error::{Error, Result};
+pub use meta::ProfileMeta;
+pub use project::ProjectFile;
+pub use rules::Rule;
+pub use session::{Overlay, OverlayKind, Session};
+pub use template::Variables;
+pub use tmux::{Reload, ReloadTargets, Reloads, Server, Tmux};
@@ -1,58 +1,879 @@
 use clap::Parser;
+use serde_json::{json, Value};
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Write},
+    process::ExitCode,
+};
 
 mod app;
 mod cli;
-mod config;
+mod editor;
+mod fuzzy;
+mod highlight;
+mod output;
 mod tui;
 
-use cli::{ensure_conf_extension, Cli, Commands};
-use config::ConfigManager;
+use cli::{ensure_conf_extension, BackupCommands, Cli, Commands, TrashCommands};
+use output::{print_result, CommandError, ErrorClass};
+use tmucks::check::IssueSource;
+use tmucks::project::PROJECT_FILE;
+use tmucks::session::Applied;
+use tmucks::settings::display_path;
+use tmucks::{
+    diff, tmux, ApplyMode, ConfigManager, Error, OverlayKind, ProfileMeta, ProjectFile, Reload,
+    Reloads, Rule, Server, Variables,
+};
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    let json = cli.json;
+    let command = cli.command.as_ref().map_or("tui", Commands::name);
 
-    match cli.command {
-        Some(Commands::List) => {
-            let config_manager = ConfigManager::new()?;
-            if config_manager.configs.is_empty() {
-                println!("No configs found in ~/.config/tmucks/");
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            if json {
+                output::print_error(command, error.as_ref());
+            } else {
+                eprintln!("Error: {}", error);
+                if let Some(hint) = hint(command, error.as_ref()) {
+                    eprintln!("  {}", hint);
+                }
+            }
+            ExitCode::from(ErrorClass::of(error.as_ref()).exit_code())
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let json = cli.json;
+    let variables: Variables = cli.variables.into_iter().collect();
+    let open = || -> tmucks::Result<ConfigManager> {
+        let mut config_manager = ConfigManager::new(cli.store.clone(), cli.target.clone())?;
+        config_manager.variables = variables.clone();
+        Ok(config_manager)
+    };
+
+    let Some(command) = cli.command else {
+        // No command provided, run TUI
+        return Ok(tui::run(open()?)?);
+    };
+    let name = command.name();
+
+    match command {
+        Commands::List { long } => {
+            let config_manager = open()?;
+            let active = config_manager.active_profile();
+            if json {
+                let mut configs = Vec::new();
+                for config in &config_manager.configs {
+                    let mut entry = serde_json::to_value(config_manager.config_meta(config)?)?;
+                    let is_active = active.as_ref().filter(|active| &active.name == config);
+                    entry["name"] = json!(config);
+                    entry["active"] = json!(is_active.is_some());
+                    entry["modified"] = json!(is_active.is_some_and(|active| active.modified));
+                    configs.push(entry);
+                }
+                print_result(
+                    name,
+                    json!({
+                        "store": config_manager.store_display(),
+                        "target": config_manager.target_display(),
+                        "linked": config_manager.linked_profile(),
+                        "configs": configs,
+                    }),
+                );
+            } else if config_manager.configs.is_empty() {
+                println!("No configs found in {}/", config_manager.store_display());
                 println!("Try setting a config using the update flag!");
             } else {
-                println!("Available configs:");
+                match config_manager.linked_profile() {
+                    Some(linked) => println!(
+                        "Available configs (target: {} -> {}):",
+                        config_manager.target_display(),
+                        linked
+                    ),
+                    None => println!(
+                        "Available configs (target: {}):",
+                        config_manager.target_display()
+                    ),
+                }
                 for config in &config_manager.configs {
-                    println!("  - {}", config);
+                    match &active {
+                        Some(active) if &active.name == config => {
+                            if active.modified {
+                                println!("  * {} (active, modified since apply)", config);
+                            } else {
+                                println!("  * {} (active)", config);
+                            }
+                        }
+                        _ => println!("  - {}", config),
+                    }
+                    if long {
+                        print_meta(&config_manager.config_meta(config)?);
+                    }
+                }
+            }
+        }
+        Commands::Apply {
+            name: config_name,
+            auto: _,
+            symlink,
+            copy,
+            force,
+            reload,
+        } => {
+            let mut config_manager = open()?;
+            config_manager.reload_targets = reload.targets();
+            if symlink {
+                config_manager.apply_mode = ApplyMode::Symlink;
+            } else if copy {
+                config_manager.apply_mode = ApplyMode::Copy;
+            }
+            match config_name {
+                Some(config_name) => {
+                    let config_name = ensure_conf_extension(config_name);
+                    apply(json, name, &config_manager, &config_name, force, None)?;
+                }
+                // clap only leaves the name out for --auto
+                None => {
+                    let rule = config_manager.auto_rule()?;
+                    let config_name = ensure_conf_extension(rule.1.profile.clone());
+                    apply(json, name, &config_manager, &config_name, force, Some(rule))?;
+                }
+            }
+        }
+        Commands::Auto {
+            dry_run,
+            force,
+            reload,
+        } => {
+            let mut config_manager = open()?;
+            config_manager.reload_targets = reload.targets();
+            let rule = config_manager.auto_rule()?;
+            let config_name = ensure_conf_extension(rule.1.profile.clone());
+            if !dry_run {
+                apply(json, name, &config_manager, &config_name, force, Some(rule))?;
+            } else if json {
+                print_result(
+                    name,
+                    json!({ "config": config_name, "rule": rule_json(rule) }),
+                );
+            } else {
+                println!("Rule {} ({}) picks {}", rule.0 + 1, rule.1, config_name);
+            }
+        }
+        Commands::Check { name: config_name } => {
+            let config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
+            let issues = config_manager.check_config(&config_name)?;
+            let result = json!({
+                "config": config_name,
+                "issues": output::issues_json(&issues),
+            });
+            if issues.is_empty() {
+                if json {
+                    print_result(name, result);
+                } else {
+                    println!("✓ No problems found in {}", config_name);
                 }
+            } else {
+                if !json {
+                    for issue in &issues {
+                        let source = match issue.source {
+                            IssueSource::Syntax => "syntax",
+                            IssueSource::Tmux => "tmux",
+                        };
+                        match issue.line {
+                            Some(line) => {
+                                println!("{}:{}: {} [{}]", config_name, line, issue.message, source)
+                            }
+                            None => println!("{}: {} [{}]", config_name, issue.message, source),
+                        }
+                    }
+                }
+                return Err(CommandError {
+                    class: ErrorClass::Invalid,
+                    message: format!("{} problem(s) found in {}", issues.len(), config_name),
+                    result,
+                }
+                .into());
             }
         }
-        Some(Commands::Apply { name }) => {
-            let config_manager = ConfigManager::new()?;
-            let config_name = ensure_conf_extension(name);
-            config_manager.apply_config(&config_name)?;
-            println!("✓ Applied config: {}", config_name);
+        Commands::Render { name: config_name } => {
+            let config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
+            let rendered = config_manager.render_config(&config_name)?;
+            if json {
+                print_result(name, json!({ "config": config_name, "contents": rendered }));
+            } else {
+                print!("{}", rendered);
+            }
         }
-        Some(Commands::Save { name }) => {
-            let config_manager = ConfigManager::new()?;
-            let config_name = ensure_conf_extension(name);
-            config_manager.save_current_config(&config_name)?;
-            println!("✓ Saved current config as: {}", config_name);
+        Commands::Save {
+            name: config_name,
+            description,
+            tags,
+        } => {
+            let config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
+            config_manager
+                .save_current_config(&config_name, &ProfileMeta::new(description, tags))?;
+            let linked = config_manager.linked_profile();
+            if json {
+                print_result(name, json!({ "config": config_name, "linked": linked }));
+            } else {
+                println!("✓ Saved current config as: {}", config_name);
+                if let Some(linked) = linked {
+                    println!(
+                        "  note: {} is symlinked to {}, the saved copy won't follow its edits",
+                        config_manager.target_display(),
+                        linked
+                    );
+                }
+            }
         }
-        Some(Commands::Update { name }) => {
-            let config_manager = ConfigManager::new()?;
-            let config_name = ensure_conf_extension(name);
-            config_manager.update_config(&config_name)?;
-            println!("+ updated config: {}", config_name);
+        Commands::Snapshot {
+            name: config_name,
+            socket,
+            description,
+            tags,
+        } => {
+            let config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
+            let server = match socket {
+                Some(socket) => Server::Socket(tmux::resolve_socket(&socket)),
+                None => Server::Default,
+            };
+            config_manager.save_snapshot(
+                &config_name,
+                &server,
+                &ProfileMeta::new(description, tags),
+            )?;
+            if json {
+                print_result(
+                    name,
+                    json!({ "config": config_name, "server": server.to_string() }),
+                );
+            } else {
+                println!("✓ Saved the running server's settings as: {}", config_name);
+            }
         }
-        Some(Commands::Delete { name }) => {
-            let config_manager = ConfigManager::new()?;
-            let config_name = ensure_conf_extension(name);
+        Commands::Update { name: config_name } => {
+            let config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
+            let linked = config_manager.linked_profile().as_deref() == Some(config_name.as_str());
+            if !linked {
+                config_manager.update_config(&config_name)?;
+            }
+            if json {
+                print_result(name, json!({ "config": config_name, "updated": !linked }));
+            } else if linked {
+                println!(
+                    "= {} is symlinked to {}, it is already up to date",
+                    config_manager.target_display(),
+                    config_name
+                );
+            } else {
+                println!("+ updated config: {}", config_name);
+            }
+        }
+        Commands::Delete { name: config_name } => {
+            let config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
             config_manager.delete_config(&config_name)?;
-            println!("✓ Deleted config: {}", config_name);
+            if json {
+                print_result(name, json!({ "config": config_name }));
+            } else {
+                println!(
+                    "✓ Moved config to trash: {} (restore with `tmucks trash restore {}`)",
+                    config_name, config_name
+                );
+            }
         }
-        None => {
-            // No command provided, run TUI
-            tui::run()?;
+        Commands::Edit { name: config_name } => {
+            let mut config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
+            let config_path = config_manager.config_path(&config_name)?;
+            let was_active = config_manager
+                .active_profile()
+                .is_some_and(|active| active.name == config_name);
+
+            let before = fs::read(&config_path)?;
+            editor::edit(&config_path)?;
+            let changed = fs::read(&config_path)? != before;
+            if json {
+                if changed {
+                    config_manager.touch_config(&config_name)?;
+                }
+                print_result(name, json!({ "config": config_name, "changed": changed }));
+                return Ok(());
+            }
+            if !changed {
+                println!("= No changes to {}", config_name);
+                return Ok(());
+            }
+            config_manager.touch_config(&config_name)?;
+            println!("✓ Edited config: {}", config_name);
+
+            // The tmux config is still the old version, unless it's a symlink
+            // to this one, and the running servers are out of date either way
+            if was_active && io::stdin().is_terminal() {
+                print!(
+                    "{} is the active config. Re-apply it and reload tmux? [y/N] ",
+                    config_name
+                );
+                io::stdout().flush()?;
+                let mut answer = String::new();
+                io::stdin().read_line(&mut answer)?;
+                if matches!(answer.trim(), "y" | "Y" | "yes") {
+                    // Keep a symlinked target linked
+                    if config_manager.linked_profile().as_deref() == Some(&config_name) {
+                        config_manager.apply_mode = ApplyMode::Symlink;
+                    }
+                    let reload = config_manager.apply_config(&config_name, false)?;
+                    println!(
+                        "✓ Applied config: {} -> {}",
+                        config_name,
+                        config_manager.target_display()
+                    );
+                    report_reload(&reload, &config_manager)?;
+                }
+            }
+        }
+        Commands::Rename { old, new, force } => {
+            let config_manager = open()?;
+            let old_name = ensure_conf_extension(old);
+            let new_name = ensure_conf_extension(new);
+            config_manager.rename_config(&old_name, &new_name, force)?;
+            if json {
+                print_result(name, json!({ "from": old_name, "to": new_name }));
+            } else {
+                println!("✓ Renamed config: {} -> {}", old_name, new_name);
+            }
+        }
+        Commands::Copy {
+            source,
+            dest,
+            force,
+        } => {
+            let config_manager = open()?;
+            let source_name = ensure_conf_extension(source);
+            let dest_name = ensure_conf_extension(dest);
+            config_manager.copy_config(&source_name, &dest_name, force)?;
+            if json {
+                print_result(name, json!({ "from": source_name, "to": dest_name }));
+            } else {
+                println!("✓ Copied config: {} -> {}", source_name, dest_name);
+            }
+        }
+        Commands::Project { session, hook } => {
+            if let Some(shell) = hook {
+                print!("{}", shell.project_hook());
+                return Ok(());
+            }
+            let config_manager = open()?;
+            let session = config_manager.session(session.as_deref())?;
+            let current = session.overlay()?;
+
+            let Some(project) = ProjectFile::find(&env::current_dir()?)? else {
+                // Outside any project, so put back what the last one changed
+                let left = match current {
+                    Some(overlay) if overlay.kind == OverlayKind::Project => {
+                        session.revert()?.map(|overlay| overlay.source)
+                    }
+                    _ => None,
+                };
+                if json {
+                    print_result(
+                        name,
+                        json!({ "session": session.name, "project": null, "left": left }),
+                    );
+                } else if let Some(left) = left {
+                    println!("✓ Left project {}, restored session {}", left, session.name);
+                } else {
+                    println!("= No {} file here or in any parent directory", PROJECT_FILE);
+                }
+                return Ok(());
+            };
+
+            let source = display_path(project.dir());
+            let mut configs = Vec::new();
+            let profile = project.profile.clone().map(ensure_conf_extension);
+            if let Some(profile) = &profile {
+                configs.push((profile.clone(), config_manager.render_config(profile)?));
+            }
+            let file = display_path(&project.path);
+            let overrides = config_manager.render_text(&file, &project.overrides)?;
+            configs.push((file, overrides));
+
+            let result = json!({ "session": session.name, "project": source, "profile": profile });
+            let unchanged = current
+                .is_some_and(|overlay| overlay.is_from(OverlayKind::Project, &source, &configs));
+            if unchanged {
+                if json {
+                    print_result(name, result);
+                } else {
+                    println!(
+                        "= Project {} is already applied to session {}",
+                        source, session.name
+                    );
+                }
+                return Ok(());
+            }
+
+            let applied = session.apply(OverlayKind::Project, &source, &configs)?;
+            if !json {
+                println!(
+                    "✓ Applied project {} to session {}: {} setting(s)",
+                    source, session.name, applied.set
+                );
+            }
+            report_applied(json, name, &applied, result)?;
+        }
+        Commands::Try {
+            name: config_name,
+            session,
+        } => {
+            let config_manager = open()?;
+            let session = config_manager.session(session.as_deref())?;
+            let config_name = ensure_conf_extension(config_name);
+            let configs = [(
+                config_name.clone(),
+                config_manager.render_config(&config_name)?,
+            )];
+            let applied = session.apply(OverlayKind::Try, &config_name, &configs)?;
+            if !json {
+                println!(
+                    "✓ Trying {} in session {}: {} setting(s) (undo with `tmucks untry`)",
+                    config_name, session.name, applied.set
+                );
+            }
+            let result = json!({ "session": session.name, "config": config_name });
+            report_applied(json, name, &applied, result)?;
+        }
+        Commands::Untry { session } => {
+            let config_manager = open()?;
+            let session = config_manager.session(session.as_deref())?;
+            // A project's overlay is left for `tmucks project` to manage
+            let tried = match session.overlay()? {
+                Some(overlay) if overlay.kind == OverlayKind::Try => {
+                    session.revert()?.map(|overlay| overlay.source)
+                }
+                _ => None,
+            };
+            if json {
+                print_result(name, json!({ "session": session.name, "config": tried }));
+            } else if let Some(tried) = tried {
+                println!("✓ Stopped trying {} in session {}", tried, session.name);
+            } else {
+                println!("= Nothing is being tried in session {}", session.name);
+            }
+        }
+        Commands::Diff {
+            name: config_name,
+            other,
+            no_color,
+        } => {
+            let config_manager = open()?;
+            let config_name = ensure_conf_extension(config_name);
+            let (old, new, new_name) = match other {
+                Some(other) => {
+                    let other_name = ensure_conf_extension(other);
+                    (
+                        config_manager.read_config(&config_name)?,
+                        config_manager.read_config(&other_name)?,
+                        other_name,
+                    )
+                }
+                // Templates are compared as they would be applied
+                None => (
+                    config_manager.render_config(&config_name)?,
+                    config_manager.read_target()?,
+                    config_manager.target_display(),
+                ),
+            };
+
+            let lines = diff::unified_diff(&old, &new, &config_name, &new_name);
+            if json {
+                let lines: Vec<String> = lines
+                    .iter()
+                    .map(|line| diff::ansi_line(line, false))
+                    .collect();
+                print_result(
+                    name,
+                    json!({ "from": config_name, "to": new_name, "diff": lines }),
+                );
+            } else if lines.is_empty() {
+                println!("No differences between {} and {}", config_name, new_name);
+            } else {
+                let color = !no_color && std::io::stdout().is_terminal();
+                for line in &lines {
+                    println!("{}", diff::ansi_line(line, color));
+                }
+            }
+        }
+        Commands::Undo { steps, reload } => {
+            let mut config_manager = open()?;
+            config_manager.reload_targets = reload.targets();
+            let undone = config_manager.undo(steps)?;
+            match undone.reloads {
+                Some(reload) => {
+                    let link = undone.link.as_deref().map(display_path);
+                    if !json {
+                        match &undone.backup {
+                            Some(backup_name) => println!(
+                                "✓ Restored {} from backup: {}",
+                                config_manager.target_display(),
+                                backup_name
+                            ),
+                            None => println!("✓ Restored {}", config_manager.target_display()),
+                        }
+                        if let Some(link) = &link {
+                            println!("  as a symlink to {}", link);
+                        }
+                    }
+                    let result = json!({
+                        "target": config_manager.target_display(),
+                        "restored": undone.backup,
+                        "link": link,
+                    });
+                    finish_reload(json, name, result, &reload, &config_manager)?;
+                }
+                None => {
+                    if json {
+                        print_result(
+                            name,
+                            json!({
+                                "target": config_manager.target_display(),
+                                "restored": null,
+                                "link": null,
+                            }),
+                        );
+                    } else {
+                        println!(
+                            "✓ Removed {}, none existed before the apply",
+                            config_manager.target_display()
+                        );
+                    }
+                }
+            }
+        }
+        Commands::Backups { command } => {
+            let mut config_manager = open()?;
+            match command.unwrap_or(BackupCommands::List) {
+                BackupCommands::List => {
+                    let backups = config_manager.list_backups()?;
+                    if json {
+                        print_result(name, json!({ "backups": backups }));
+                    } else if backups.is_empty() {
+                        println!(
+                            "No backups found in {}/backups/",
+                            config_manager.store_display()
+                        );
+                    } else {
+                        println!("Available backups (newest first):");
+                        for backup in &backups {
+                            println!("  - {}", backup);
+                        }
+                    }
+                }
+                BackupCommands::Show { name: backup_name } => {
+                    let backup_name = ensure_conf_extension(backup_name);
+                    let contents = config_manager.read_backup(&backup_name)?;
+                    if json {
+                        print_result(name, json!({ "backup": backup_name, "contents": contents }));
+                    } else {
+                        print!("{}", contents);
+                    }
+                }
+                BackupCommands::Restore {
+                    name: backup_name,
+                    reload,
+                } => {
+                    config_manager.reload_targets = reload.targets();
+                    let backup_name = ensure_conf_extension(backup_name);
+                    let reload = config_manager.restore_backup(&backup_name)?;
+                    if !json {
+                        println!("✓ Restored backup: {}", backup_name);
+                    }
+                    let result = json!({
+                        "backup": backup_name,
+                        "target": config_manager.target_display(),
+                    });
+                    finish_reload(json, name, result, &reload, &config_manager)?;
+                }
+                BackupCommands::Prune { keep } => {
+                    let keep = keep.unwrap_or(config_manager.backup_limit);
+                    let removed = config_manager.prune_backups(keep)?;
+                    if json {
+                        print_result(name, json!({ "removed": removed }));
+                    } else {
+                        println!("✓ Pruned {} backup(s)", removed);
+                    }
+                }
+            }
+        }
+        Commands::Trash { command } => {
+            let config_manager = open()?;
+            match command.unwrap_or(TrashCommands::List) {
+                TrashCommands::List => {
+                    let entries = config_manager.list_trash()?;
+                    if json {
+                        let entries: Vec<Value> = entries
+                            .iter()
+                            .map(|entry| {
+                                json!({
+                                    "name": entry.name,
+                                    "deleted": entry.deleted_at(),
+                                })
+                            })
+                            .collect();
+                        print_result(name, json!({ "configs": entries }));
+                    } else if entries.is_empty() {
+                        println!("The trash is empty");
+                    } else {
+                        println!("Deleted configs (most recent first):");
+                        for entry in &entries {
+                            println!("  - {} (deleted {})", entry.name, entry.deleted_at());
+                        }
+                    }
+                }
+                TrashCommands::Restore {
+                    name: config_name,
+                    force,
+                } => {
+                    let config_name = ensure_conf_extension(config_name);
+                    config_manager.restore_from_trash(&config_name, force)?;
+                    if json {
+                        print_result(name, json!({ "config": config_name }));
+                    } else {
+                        println!("✓ Restored config from trash: {}", config_name);
+                    }
+                }
+                TrashCommands::Empty => {
+                    let removed = config_manager.empty_trash()?;
+                    if json {
+                        print_result(name, json!({ "removed": removed }));
+                    } else {
+                        println!("✓ Removed {} config(s) from the trash", removed);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A suggestion printed under an error that has an obvious way out
+fn hint(command: &str, error: &(dyn std::error::Error + 'static)) -> Option<&'static str> {
+    match (command, error.downcast_ref::<Error>()?) {
+        ("save", Error::ConfigExists(_)) => Some("use `tmucks update` to overwrite it"),
+        ("update", Error::ConfigNotFound(_)) => Some("use `tmucks save` to create it"),
+        (_, Error::ConfigExists(_)) => Some("use --force to overwrite it"),
+        (_, Error::MissingVariables { .. }) => Some(
+            "set them in variables.toml in the store, as TMUCKS_VAR_<NAME> or with --set NAME=VALUE",
+        ),
+        (_, Error::TemplateUpdate(_)) => Some("use `tmucks edit` to change it"),
+        (_, Error::NoRuleMatched) => {
+            Some("add a [[rule]] with a profile to settings.toml in the store, or one without conditions as a fallback")
+        }
+        _ => None,
+    }
+}
+
+/// Prints the metadata lines under a config in `list --long`
+fn print_meta(meta: &ProfileMeta) {
+    if let Some(description) = &meta.description {
+        println!("      {}", description);
+    }
+    if !meta.tags.is_empty() {
+        println!("      tags: {}", meta.tags.join(", "));
+    }
+    if let Some(author) = &meta.author {
+        println!("      author: {}", author);
+    }
+    if let Some(created) = &meta.created {
+        println!("      created: {}", created);
+    }
+    if let Some(updated) = &meta.updated {
+        println!("      updated: {}", updated);
+    }
+    if let Some(tmux_version) = &meta.tmux_version {
+        println!("      tmux: {}", tmux_version);
+    }
+}
+
+/// Applies a saved config and reports how it went, along with the rule that
+/// picked it when it was chosen automatically
+fn apply(
+    json: bool,
+    command: &str,
+    config_manager: &ConfigManager,
+    config_name: &str,
+    force: bool,
+    rule: Option<(usize, &Rule)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let (Some((index, rule)), false) = (rule, json) {
+        println!("Rule {} ({}) picks {}", index + 1, rule, config_name);
+    }
+    let reload = config_manager.apply_config(config_name, force)?;
+    // Templates are written out even in symlink mode
+    let symlinked = config_manager.linked_profile().is_some();
+    if !json {
+        println!(
+            "✓ Applied config: {} -> {}{}",
+            config_name,
+            config_manager.target_display(),
+            if symlinked { " (symlink)" } else { "" }
+        );
+    }
+    let mut result = json!({
+        "config": config_name,
+        "target": config_manager.target_display(),
+        "mode": if symlinked { "symlink" } else { "copy" },
+    });
+    if let Some(rule) = rule {
+        result["rule"] = rule_json(rule);
+    }
+    finish_reload(json, command, result, &reload, config_manager)
+}
+
+/// A rule by its 1-based position in the settings file and its conditions
+fn rule_json((index, rule): (usize, &Rule)) -> Value {
+    json!({ "index": index + 1, "conditions": rule.to_string() })
+}
+
+/// Lists the lines that could not be set on a session. Lines tmux refused
+/// make the command fail.
+fn report_applied(
+    json: bool,
+    command: &str,
+    applied: &Applied,
+    mut result: Value,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        result["set"] = json!(applied.set);
+        result["skipped"] = output::line_reports_json(&applied.skipped);
+        result["failed"] = output::line_reports_json(&applied.failed);
+    } else {
+        for skipped in &applied.skipped {
+            println!(
+                "  - skipped {}:{}: {}",
+                skipped.source, skipped.line, skipped.message
+            );
+        }
+        for failed in &applied.failed {
+            println!("  ✗ {}:{}: {}", failed.source, failed.line, failed.message);
         }
     }
 
+    if !applied.failed.is_empty() {
+        return Err(CommandError {
+            class: ErrorClass::Tmux,
+            message: format!("tmux refused {} line(s)", applied.failed.len()),
+            result,
+        }
+        .into());
+    }
+    if json {
+        print_result(command, result);
+    }
     Ok(())
 }
+
+/// Finishes a command that reloaded tmux. In JSON mode how each server went
+/// is added to `result` before printing it.
+fn finish_reload(
+    json: bool,
+    command: &str,
+    mut result: Value,
+    reloads: &Reloads,
+    config_manager: &ConfigManager,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !json {
+        return report_reload(reloads, config_manager);
+    }
+
+    result["reloads"] = output::reloads_json(reloads, config_manager);
+    match reload_failure(reloads) {
+        Some(message) => Err(CommandError {
+            class: ErrorClass::Tmux,
+            message,
+            result,
+        }
+        .into()),
+        None => {
+            print_result(command, result);
+            Ok(())
+        }
+    }
+}
+
+/// Tells the user how reloading each tmux server went. Errors from tmux are
+/// listed and turned into a failure so scripts notice them.
+fn report_reload(
+    reloads: &Reloads,
+    config_manager: &ConfigManager,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if reloads.is_empty() {
+        println!("= No running tmux servers found, nothing was reloaded");
+        return Ok(());
+    }
+
+    for (server, reload) in reloads {
+        // Name the server unless it's the usual lone default one
+        let on = match server {
+            Server::Default => String::new(),
+            Server::Socket(_) => format!(" on {}", server),
+        };
+        match reload {
+            Reload::Reloaded => println!("✓ Reloaded tmux{}", on),
+            Reload::NotInstalled => println!("! tmux is not installed, nothing was reloaded"),
+            Reload::NoServer => println!(
+                "= No tmux server running{}, the config will load with the next session",
+                on
+            ),
+            Reload::ConfigErrors(issues) => {
+                eprintln!("✗ tmux{} reported errors while reloading:", on);
+                for issue in issues {
+                    match issue.line {
+                        Some(line) => eprintln!(
+                            "  {}:{}: {}",
+                            config_manager.target_display(),
+                            line,
+                            issue.message
+                        ),
+                        None => eprintln!("  {}", issue.message),
+                    }
+                }
+            }
+        }
+    }
+
+    match reload_failure(reloads) {
+        Some(message) => Err(CommandError {
+            class: ErrorClass::Tmux,
+            message,
+            result: Value::Null,
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// The error to fail with when tmux reported errors on any server
+fn reload_failure(reloads: &Reloads) -> Option<String> {
+    let failed = reloads
+        .iter()
+        .filter(|(_, reload)| matches!(reload, Reload::ConfigErrors(_)))
+        .count();
+    (failed > 0).then(|| {
+        format!(
+            "tmux reported errors on {} server(s) while reloading",
+            failed
+        )
+    })
+}
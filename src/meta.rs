@@ -0,0 +1,99 @@
+//! Descriptions, tags and history kept next to saved configs.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Error, Result};
+
+/// Optional details about a saved config, kept next to it in the store as
+/// `<config>.toml`
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProfileMeta {
+    /// What the config is for
+    pub description: Option<String>,
+    /// Tags to file the config under
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// `$USER` when the config was saved
+    pub author: Option<String>,
+    /// When the config was first saved, as `YYYY-MM-DD HH:MM:SS` local time
+    pub created: Option<String>,
+    /// When the config's contents last changed
+    pub updated: Option<String>,
+    /// tmux version the config was last saved from, as reported by `tmux -V`
+    pub tmux_version: Option<String>,
+}
+
+impl ProfileMeta {
+    /// Metadata for a config saved just now. The tmux version is filled in
+    /// when the config is saved.
+    pub fn new(description: Option<String>, tags: Vec<String>) -> Self {
+        let now = timestamp();
+        Self {
+            description,
+            tags,
+            author: env::var("USER").ok().filter(|user| !user.is_empty()),
+            created: Some(now.clone()),
+            updated: Some(now),
+            tmux_version: None,
+        }
+    }
+
+    /// Records that the config's contents changed just now
+    pub fn touch(&mut self) {
+        self.updated = Some(timestamp());
+    }
+
+    /// Whether nothing at all is recorded
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none()
+            && self.tags.is_empty()
+            && self.author.is_none()
+            && self.created.is_none()
+            && self.updated.is_none()
+            && self.tmux_version.is_none()
+    }
+
+    /// Reads the metadata for the config at `config_path`, which is empty if
+    /// none was recorded
+    pub fn load(config_path: &Path) -> Result<Self> {
+        let path = meta_path(config_path);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        toml::from_str(&contents).map_err(|e| Error::InvalidFile {
+            path,
+            message: e.to_string(),
+        })
+    }
+
+    /// Writes the metadata for the config at `config_path`
+    pub fn save(&self, config_path: &Path) -> Result<()> {
+        let path = meta_path(config_path);
+        let contents = toml::to_string(self).map_err(|e| Error::InvalidFile {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// Where the metadata for the config at `config_path` is kept
+pub fn meta_path(config_path: &Path) -> PathBuf {
+    let mut path = config_path.as_os_str().to_owned();
+    path.push(".toml");
+    PathBuf::from(path)
+}
+
+fn timestamp() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
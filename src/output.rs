@@ -0,0 +1,255 @@
+use serde_json::{json, Map, Value};
+use std::{error::Error, fmt, io};
+
+use tmucks::check::{Issue, IssueSource};
+use tmucks::config::ConfigManager;
+use tmucks::error::Error as TmucksError;
+use tmucks::session::LineReport;
+use tmucks::tmux::{Reload, Reloads, Server};
+
+/// Exit codes, one per class of failure, listed in `tmucks --help`
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  success
+  1  any other failure
+  2  invalid command line
+  3  a config, backup, trashed config, tmux session or the tmux config was not
+     found, or no auto rule matched
+  4  a config with that name already exists
+  5  a config, its metadata, its variables or the settings file has errors
+  6  tmux reported errors while reloading, or could not be run to check
+  7  a file could not be read or written";
+
+/// What kind of failure ended a command, which decides the exit code
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorClass {
+    Failure,
+    NotFound,
+    AlreadyExists,
+    Invalid,
+    Tmux,
+    Io,
+}
+
+impl ErrorClass {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorClass::Failure => 1,
+            ErrorClass::NotFound => 3,
+            ErrorClass::AlreadyExists => 4,
+            ErrorClass::Invalid => 5,
+            ErrorClass::Tmux => 6,
+            ErrorClass::Io => 7,
+        }
+    }
+
+    /// The `kind` reported in JSON errors
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorClass::Failure => "failure",
+            ErrorClass::NotFound => "not_found",
+            ErrorClass::AlreadyExists => "already_exists",
+            ErrorClass::Invalid => "invalid",
+            ErrorClass::Tmux => "tmux",
+            ErrorClass::Io => "io",
+        }
+    }
+
+    pub fn of(error: &(dyn Error + 'static)) -> Self {
+        if let Some(error) = error.downcast_ref::<CommandError>() {
+            return error.class;
+        }
+        if error.downcast_ref::<io::Error>().is_some() {
+            return ErrorClass::Io;
+        }
+        match error.downcast_ref::<TmucksError>() {
+            Some(
+                TmucksError::ConfigNotFound(_)
+                | TmucksError::NoTmuxConfig(_)
+                | TmucksError::BackupNotFound(_)
+                | TmucksError::BackupPruned(_)
+                | TmucksError::NotInTrash(_)
+                | TmucksError::NoRuleMatched
+                | TmucksError::NoSession(_),
+            ) => ErrorClass::NotFound,
+            Some(TmucksError::ConfigExists(_) | TmucksError::TargetIsLink { .. }) => {
+                ErrorClass::AlreadyExists
+            }
+            Some(
+                TmucksError::ConfigErrors { .. }
+                | TmucksError::MissingVariables { .. }
+                | TmucksError::InvalidFile { .. },
+            ) => ErrorClass::Invalid,
+            Some(TmucksError::Tmux(_)) => ErrorClass::Tmux,
+            Some(TmucksError::Io(_)) => ErrorClass::Io,
+            _ => ErrorClass::Failure,
+        }
+    }
+}
+
+/// A command failed after getting far enough to have something to report,
+/// like a check listing problems or an apply whose reload went wrong. In JSON
+/// mode `result` is printed along with the error.
+#[derive(Debug)]
+pub struct CommandError {
+    pub class: ErrorClass,
+    pub message: String,
+    pub result: Value,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for CommandError {}
+
+/// Prints the JSON result of a command that succeeded
+pub fn print_result(command: &str, result: Value) {
+    let mut object = Map::new();
+    object.insert("ok".into(), Value::Bool(true));
+    object.insert("command".into(), Value::String(command.into()));
+    if let Value::Object(fields) = result {
+        object.extend(fields);
+    }
+    println!("{}", Value::Object(object));
+}
+
+/// Prints the JSON for a command that failed, with whatever it had to report
+pub fn print_error(command: &str, error: &(dyn Error + 'static)) {
+    let class = ErrorClass::of(error);
+    let mut object = Map::new();
+    object.insert("ok".into(), Value::Bool(false));
+    object.insert("command".into(), Value::String(command.into()));
+    if let Some(Value::Object(fields)) = error
+        .downcast_ref::<CommandError>()
+        .map(|error| error.result.clone())
+    {
+        object.extend(fields);
+    }
+    let mut details = json!({
+        "kind": class.name(),
+        "message": error.to_string(),
+        "exit_code": class.exit_code(),
+    });
+    if let Some(TmucksError::ConfigErrors { name, issues }) = error.downcast_ref() {
+        details["config"] = json!(name);
+        details["issues"] = issues_json(issues);
+    }
+    if let Some(TmucksError::MissingVariables { name, missing }) = error.downcast_ref() {
+        details["config"] = json!(name);
+        details["missing"] = json!(missing);
+    }
+    object.insert("error".into(), details);
+    println!("{}", Value::Object(object));
+}
+
+pub fn issues_json(issues: &[Issue]) -> Value {
+    issues
+        .iter()
+        .map(|issue| {
+            json!({
+                "line": issue.line,
+                "message": issue.message,
+                "source": match issue.source {
+                    IssueSource::Syntax => "syntax",
+                    IssueSource::Tmux => "tmux",
+                },
+            })
+        })
+        .collect()
+}
+
+pub fn line_reports_json(reports: &[LineReport]) -> Value {
+    reports
+        .iter()
+        .map(|report| {
+            json!({
+                "source": report.source,
+                "line": report.line,
+                "message": report.message,
+            })
+        })
+        .collect()
+}
+
+pub fn reloads_json(reloads: &Reloads, config_manager: &ConfigManager) -> Value {
+    reloads
+        .iter()
+        .map(|(server, reload)| {
+            let server = match server {
+                Server::Default => Value::String("default".into()),
+                Server::Socket(socket) => Value::String(socket.display().to_string()),
+            };
+            match reload {
+                Reload::Reloaded => json!({ "server": server, "status": "reloaded" }),
+                Reload::NotInstalled => json!({ "server": server, "status": "not_installed" }),
+                Reload::NoServer => json!({ "server": server, "status": "no_server" }),
+                Reload::ConfigErrors(issues) => json!({
+                    "server": server,
+                    "status": "errors",
+                    "target": config_manager.target_display(),
+                    "errors": issues_json(issues),
+                }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn class_of(error: impl Error + 'static) -> ErrorClass {
+        let error: Box<dyn Error> = Box::new(error);
+        ErrorClass::of(error.as_ref())
+    }
+
+    #[test]
+    fn errors_map_to_their_exit_codes() {
+        let cases = [
+            (class_of(TmucksError::ConfigNotFound("a.conf".into())), 3),
+            (class_of(TmucksError::BackupPruned("b.conf".into())), 3),
+            (class_of(TmucksError::NoSession(None)), 3),
+            (class_of(TmucksError::NoRuleMatched), 3),
+            (class_of(TmucksError::ConfigExists("a.conf".into())), 4),
+            (
+                class_of(TmucksError::TargetIsLink {
+                    path: PathBuf::from("/home/me/.tmux.conf"),
+                    link: PathBuf::from("/home/me/dotfiles/tmux.conf"),
+                }),
+                4,
+            ),
+            (
+                class_of(TmucksError::MissingVariables {
+                    name: "a.conf".into(),
+                    missing: vec!["accent".into()],
+                }),
+                5,
+            ),
+            (class_of(TmucksError::Tmux("no server".into())), 6),
+            (class_of(TmucksError::Io(io::ErrorKind::NotFound.into())), 7),
+            (
+                class_of(io::Error::from(io::ErrorKind::PermissionDenied)),
+                7,
+            ),
+            (class_of(TmucksError::NothingToUndo), 1),
+        ];
+        for (class, code) in cases {
+            assert_eq!(class.exit_code(), code, "{:?}", class);
+        }
+    }
+
+    #[test]
+    fn command_errors_keep_their_class() {
+        let error = CommandError {
+            class: ErrorClass::Invalid,
+            message: String::from("2 problem(s) found in a.conf"),
+            result: Value::Null,
+        };
+        assert_eq!(class_of(error), ErrorClass::Invalid);
+        assert_eq!(ErrorClass::Invalid.name(), "invalid");
+    }
+}
@@ -0,0 +1,1278 @@
+//! A parser for tmux config files that keeps every line, so files can
+//! be read, inspected and written back unchanged.
+
+use std::fmt;
+
+/// A parsed tmux config file. Every source line is kept, so writing the file
+/// back out with `to_string` reproduces the input exactly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigFile {
+    /// Every logical line, in order
+    pub lines: Vec<ConfigLine>,
+    /// Problems found while parsing, in line order
+    pub errors: Vec<ParseError>,
+    trailing_newline: bool,
+}
+
+/// One logical line: a single source line, or several joined by a trailing
+/// backslash or an open `{` block
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigLine {
+    /// 1-based number of the first source line
+    pub number: usize,
+    /// Source text including any continuation lines, without the final newline
+    pub raw: String,
+    /// What the line holds
+    pub kind: LineKind,
+}
+
+/// What a logical line holds
+#[derive(Clone, Debug, PartialEq)]
+pub enum LineKind {
+    /// Nothing but whitespace
+    Blank,
+    /// A whole-line comment, without the leading `#`
+    Comment(String),
+    /// One or more commands separated by `;`, with an optional trailing comment
+    Commands {
+        /// The commands, in order
+        commands: Vec<Command>,
+        /// The trailing comment, without the leading `#`
+        comment: Option<String>,
+    },
+    /// A `%` directive
+    Directive(Directive),
+    /// A line that could not be parsed; the reason is in `ConfigFile::errors`
+    Invalid,
+}
+
+/// Parser directives, the lines starting with `%`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Directive {
+    /// `%if` with its format condition
+    If(String),
+    /// `%elif` with its format condition
+    Elif(String),
+    /// `%else`
+    Else,
+    /// `%endif`
+    Endif,
+    /// `%hidden NAME=value`, an assignment tmux doesn't pass on to the
+    /// environment
+    Hidden {
+        /// The variable's name
+        name: String,
+        /// Its value, unquoted
+        value: String,
+    },
+}
+
+/// A problem found while parsing
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// What is wrong
+    pub message: String,
+}
+
+/// Command line flags, split into switches like `-g` and flags that take a
+/// value like `-t target`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Flags {
+    /// Switches in the order given, like `['g', 'a']` for `-ga`
+    pub switches: Vec<char>,
+    /// Flags with their values, in the order given
+    pub values: Vec<(char, String)>,
+}
+
+/// A tmux command. The ones tmucks looks into are typed; the rest are kept
+/// as their arguments. Every variant keeps the command name as written, so
+/// `set` stays `set` rather than becoming `set-option`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// `set-option`/`set`/`set-window-option`/`setw`
+    SetOption {
+        /// The command name as written
+        name: String,
+        /// `-g`, `-w`, `-t target` and so on
+        flags: Flags,
+        /// The option's name, with an `[index]` for array options
+        option: String,
+        /// The new value; absent for `-u` and for toggling flag options
+        value: Option<String>,
+    },
+    /// `bind-key`/`bind`, with the commands the key runs
+    Bind {
+        /// The command name as written
+        name: String,
+        /// `-n`, `-r`, `-T table` and `-N note`
+        flags: Flags,
+        /// The key, like `C-a` or `M-Left`
+        key: String,
+        /// The bound commands, from a `\;` separated list or a `{ }` block
+        commands: Vec<Command>,
+    },
+    /// `unbind-key`/`unbind`; the key is absent with `-a`
+    Unbind {
+        /// The command name as written
+        name: String,
+        /// `-a`, `-n`, `-T table` and so on
+        flags: Flags,
+        /// The key to unbind
+        key: Option<String>,
+    },
+    /// `source-file`/`source`
+    SourceFile {
+        /// The command name as written
+        name: String,
+        /// `-q`, `-F` and so on
+        flags: Flags,
+        /// The files to source, which may be globs
+        paths: Vec<String>,
+    },
+    /// `if-shell`/`if`
+    IfShell {
+        /// The command name as written
+        name: String,
+        /// `-F`, `-b` and `-t target`
+        flags: Flags,
+        /// A shell command, or a format with `-F`
+        condition: String,
+        /// Commands run when the condition holds
+        then_command: String,
+        /// Commands run when it doesn't
+        else_command: Option<String>,
+    },
+    /// `NAME=value` environment assignment
+    Assignment {
+        /// The variable's name
+        name: String,
+        /// Its value, unquoted
+        value: String,
+    },
+    /// Any other command, kept as its arguments
+    Other {
+        /// The command name as written
+        name: String,
+        /// Its arguments, unquoted
+        args: Vec<String>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    /// The contents of a `{ }` block, which tmux reads as a command list
+    Block(String),
+    Separator,
+}
+
+impl Flags {
+    /// Whether the switch was given, like `has('g')` for `-g`
+    pub fn has(&self, switch: char) -> bool {
+        self.switches.contains(&switch)
+    }
+
+    /// The value given with a flag, like the table for `-T`
+    pub fn value(&self, flag: char) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(c, _)| *c == flag)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether no flags were given at all
+    pub fn is_empty(&self) -> bool {
+        self.switches.is_empty() && self.values.is_empty()
+    }
+}
+
+impl ConfigFile {
+    /// Every top-level command with the line it starts on. Commands bound to
+    /// keys are not included; they live inside `Command::Bind`.
+    pub fn commands(&self) -> impl Iterator<Item = (usize, &Command)> {
+        self.lines.iter().flat_map(|line| {
+            let commands: &[Command] = match &line.kind {
+                LineKind::Commands { commands, .. } => commands,
+                _ => &[],
+            };
+            commands.iter().map(move |command| (line.number, command))
+        })
+    }
+}
+
+/// Parses a tmux config file. Parsing never fails outright: lines that can't
+/// be understood become `LineKind::Invalid` and are reported in `errors`.
+pub fn parse(input: &str) -> ConfigFile {
+    let mut lines = Vec::new();
+    let mut errors = Vec::new();
+    let mut conditionals: Vec<(usize, bool)> = Vec::new();
+
+    let trailing_newline = input.ends_with('\n');
+    let body = input.strip_suffix('\n').unwrap_or(input);
+    let source_lines: Vec<&str> = if input.is_empty() {
+        Vec::new()
+    } else {
+        body.split('\n').collect()
+    };
+
+    let mut index = 0;
+    while index < source_lines.len() {
+        let number = index + 1;
+        let mut raw = source_lines[index].to_string();
+        index += 1;
+
+        // Join continuation lines and multi-line { } blocks
+        while index < source_lines.len() && needs_continuation(&raw) {
+            raw.push('\n');
+            raw.push_str(source_lines[index]);
+            index += 1;
+        }
+
+        let kind = match parse_line(&raw) {
+            Ok(kind) => kind,
+            Err(message) => {
+                errors.push(ParseError {
+                    line: number,
+                    message,
+                });
+                LineKind::Invalid
+            }
+        };
+
+        // Track %if nesting; the flag records whether %else was seen
+        if let LineKind::Directive(directive) = &kind {
+            let misplaced = match directive {
+                Directive::If(_) => {
+                    conditionals.push((number, false));
+                    None
+                }
+                Directive::Elif(_) => match conditionals.last() {
+                    None => Some("%elif without %if"),
+                    Some((_, true)) => Some("%elif after %else"),
+                    Some(_) => None,
+                },
+                Directive::Else => match conditionals.last_mut() {
+                    None => Some("%else without %if"),
+                    Some((_, true)) => Some("duplicate %else"),
+                    Some((_, seen_else)) => {
+                        *seen_else = true;
+                        None
+                    }
+                },
+                Directive::Endif => match conditionals.pop() {
+                    None => Some("%endif without %if"),
+                    Some(_) => None,
+                },
+                Directive::Hidden { .. } => None,
+            };
+            if let Some(message) = misplaced {
+                errors.push(ParseError {
+                    line: number,
+                    message: message.to_string(),
+                });
+            }
+        }
+
+        lines.push(ConfigLine { number, raw, kind });
+    }
+
+    for (line, _) in conditionals {
+        errors.push(ParseError {
+            line,
+            message: String::from("%if without matching %endif"),
+        });
+    }
+    errors.sort_by_key(|error| error.line);
+
+    ConfigFile {
+        lines,
+        errors,
+        trailing_newline,
+    }
+}
+
+/// Whether a word starting with `c`, followed by `next`, is a comment. A `#`
+/// starts one unless it opens a `#{}` format.
+pub fn starts_comment(c: char, next: Option<char>) -> bool {
+    c == '#' && next != Some('{')
+}
+
+/// Parses a command string such as the argument of `if-shell` or the body of
+/// a `{ }` block
+pub fn parse_commands(input: &str) -> Result<Vec<Command>, String> {
+    let (tokens, _) = tokenize(input)?;
+    commands_from_tokens(tokens)
+}
+
+fn needs_continuation(raw: &str) -> bool {
+    let trailing_backslashes = raw.chars().rev().take_while(|c| *c == '\\').count();
+    trailing_backslashes % 2 == 1 || brace_depth(raw) > 0
+}
+
+/// Counts unclosed `{` outside quotes and comments
+fn brace_depth(text: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut quote = None;
+    let mut chars = text.chars().peekable();
+    let mut at_word_start = true;
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some('\'') => {
+                if c == '\'' {
+                    quote = None;
+                }
+            }
+            Some(_) => match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => quote = None,
+                _ => {}
+            },
+            None => match c {
+                '\\' => {
+                    chars.next();
+                }
+                '\'' | '"' => quote = Some(c),
+                c if at_word_start && starts_comment(c, chars.peek().copied()) => {
+                    // Skip the comment up to the end of this source line
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                '{' if at_word_start => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            },
+        }
+        at_word_start = quote.is_none() && (c.is_whitespace() || c == ';' || c == '{');
+    }
+
+    depth
+}
+
+fn parse_line(raw: &str) -> Result<LineKind, String> {
+    let trimmed = raw.trim();
+
+    if trimmed.is_empty() {
+        return Ok(LineKind::Blank);
+    }
+    if let Some(comment) = trimmed.strip_prefix('#') {
+        if !comment.contains('\n') {
+            return Ok(LineKind::Comment(comment.to_string()));
+        }
+    }
+    if trimmed.starts_with('%') {
+        return parse_directive(trimmed).map(LineKind::Directive);
+    }
+
+    let (tokens, comment) = tokenize(raw)?;
+    let commands = commands_from_tokens(tokens)?;
+    Ok(LineKind::Commands { commands, comment })
+}
+
+fn parse_directive(text: &str) -> Result<Directive, String> {
+    let (keyword, rest) = match text.find(char::is_whitespace) {
+        Some(i) => (&text[..i], text[i..].trim()),
+        None => (text, ""),
+    };
+
+    let single_argument = |keyword: &str| -> Result<String, String> {
+        let (tokens, _) = tokenize(rest)?;
+        let mut words = words_only(tokens, keyword)?;
+        if words.len() != 1 {
+            return Err(format!("{} takes exactly one condition", keyword));
+        }
+        Ok(words.remove(0))
+    };
+
+    match keyword {
+        "%if" => Ok(Directive::If(single_argument("%if")?)),
+        "%elif" => Ok(Directive::Elif(single_argument("%elif")?)),
+        "%else" => Ok(Directive::Else),
+        "%endif" => Ok(Directive::Endif),
+        "%hidden" => {
+            let assignment = single_argument("%hidden")?;
+            match split_assignment(&assignment) {
+                Some((name, value)) => Ok(Directive::Hidden { name, value }),
+                None => Err(String::from("%hidden expects NAME=value")),
+            }
+        }
+        _ => Err(format!("unknown directive: {}", keyword)),
+    }
+}
+
+fn words_only(tokens: Vec<Token>, context: &str) -> Result<Vec<String>, String> {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            Token::Word(word) | Token::Block(word) => Ok(word),
+            Token::Separator => Err(format!("unexpected ';' in {}", context)),
+        })
+        .collect()
+}
+
+fn split_assignment(word: &str) -> Option<(String, String)> {
+    let (name, value) = word.split_once('=')?;
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    if !(first.is_ascii_alphabetic() || first == '_')
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    Some((name.to_string(), value.to_string()))
+}
+
+/// Splits text into words, `{ }` blocks, separators and a trailing comment,
+/// following tmux's quoting rules. Inside a block a newline separates
+/// commands like `;` does.
+fn tokenize(text: &str) -> Result<(Vec<Token>, Option<String>), String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        // Whitespace, including a backslash-newline continuation
+        if c == '\n' {
+            tokens.push(Token::Separator);
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '\\' && chars.get(i + 1) == Some(&'\n') {
+            i += 2;
+            continue;
+        }
+        // A word starting with # is a comment, unless it is a #{} format.
+        // Comments on the lines of a block end with the line.
+        if starts_comment(c, chars.get(i + 1).copied()) {
+            match chars[i..].iter().position(|c| *c == '\n') {
+                Some(end) => {
+                    i += end;
+                    continue;
+                }
+                None => {
+                    let comment: String = chars[i + 1..].iter().collect();
+                    return Ok((tokens, Some(comment)));
+                }
+            }
+        }
+        if c == ';' {
+            tokens.push(Token::Separator);
+            i += 1;
+            continue;
+        }
+        if c == '{' {
+            let (block, next) = read_block(&chars, i)?;
+            tokens.push(Token::Block(block));
+            i = next;
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut ends_command = false;
+        let mut escaped_separator = false;
+        while i < chars.len() && !chars[i].is_whitespace() {
+            match chars[i] {
+                '\'' => {
+                    let end = chars[i + 1..]
+                        .iter()
+                        .position(|c| *c == '\'')
+                        .ok_or("unterminated single quote")?;
+                    word.extend(&chars[i + 1..i + 1 + end]);
+                    i += end + 2;
+                }
+                '"' => {
+                    i += 1;
+                    loop {
+                        match chars.get(i) {
+                            None => return Err(String::from("unterminated double quote")),
+                            Some('"') => break,
+                            Some('\\') => {
+                                match chars.get(i + 1) {
+                                    Some(escaped @ ('"' | '\\' | '$')) => word.push(*escaped),
+                                    Some('\n') => {}
+                                    Some(other) => {
+                                        word.push('\\');
+                                        word.push(*other);
+                                    }
+                                    None => word.push('\\'),
+                                }
+                                i += 2;
+                                continue;
+                            }
+                            Some(c) => word.push(*c),
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                '\\' => {
+                    match chars.get(i + 1) {
+                        // Backslash-newline continues the line
+                        Some('\n') => {}
+                        // `word\;` ends the word with a separator, as in
+                        // `bind y set synchronize-panes\; display ...`
+                        Some(';')
+                            if !word.is_empty()
+                                && chars.get(i + 2).is_none_or(|c| c.is_whitespace()) =>
+                        {
+                            escaped_separator = true;
+                        }
+                        Some(escaped) => word.push(*escaped),
+                        None => {}
+                    }
+                    i += 2;
+                }
+                ';' => {
+                    ends_command = true;
+                    i += 1;
+                    break;
+                }
+                c => {
+                    word.push(c);
+                    i += 1;
+                }
+            }
+        }
+
+        tokens.push(Token::Word(word));
+        if escaped_separator {
+            tokens.push(Token::Word(String::from(";")));
+        }
+        if ends_command {
+            tokens.push(Token::Separator);
+        }
+    }
+
+    Ok((tokens, None))
+}
+
+/// Reads a `{ }` block starting at `start`, returning its trimmed contents
+/// and the index just past the closing brace
+fn read_block(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut at_word_start = true;
+    let mut i = start;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match quote {
+            Some('\'') => {
+                if c == '\'' {
+                    quote = None;
+                }
+            }
+            Some(_) => match c {
+                '\\' => i += 1,
+                '"' => quote = None,
+                _ => {}
+            },
+            None => match c {
+                '\\' => i += 1,
+                '\'' | '"' => quote = Some(c),
+                c if at_word_start && starts_comment(c, chars.get(i + 1).copied()) => {
+                    // Skip the comment up to the end of this source line
+                    while i + 1 < chars.len() && chars[i + 1] != '\n' {
+                        i += 1;
+                    }
+                }
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let inner: String = chars[start + 1..i].iter().collect();
+                        return Ok((inner.trim().to_string(), i + 1));
+                    }
+                }
+                _ => {}
+            },
+        }
+        at_word_start = quote.is_none() && (c.is_whitespace() || c == ';' || c == '{');
+        i += 1;
+    }
+
+    Err(String::from("unterminated { block"))
+}
+
+fn commands_from_tokens(tokens: Vec<Token>) -> Result<Vec<Command>, String> {
+    let mut commands = Vec::new();
+    let mut args = Vec::new();
+
+    for token in tokens.into_iter().chain(std::iter::once(Token::Separator)) {
+        match token {
+            Token::Word(_) | Token::Block(_) => args.push(token),
+            Token::Separator => {
+                if !args.is_empty() {
+                    commands.push(parse_command(std::mem::take(&mut args))?);
+                }
+            }
+        }
+    }
+
+    Ok(commands)
+}
+
+/// Builds a typed command from its arguments, the first being the name
+fn parse_command(args: Vec<Token>) -> Result<Command, String> {
+    let ends_with_block = matches!(args.last(), Some(Token::Block(_)));
+    let mut args: Vec<String> = args
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Word(word) | Token::Block(word) => Some(word),
+            Token::Separator => None,
+        })
+        .collect();
+    let name = args.remove(0);
+
+    if args.is_empty() {
+        if let Some((var, value)) = split_assignment(&name) {
+            return Ok(Command::Assignment { name: var, value });
+        }
+    }
+
+    match name.as_str() {
+        "set-option" | "set" | "set-window-option" | "setw" => {
+            let (flags, mut rest) = parse_flags(args, "t", &name)?;
+            if rest.is_empty() {
+                return Err(format!("{}: missing option name", name));
+            }
+            let option = rest.remove(0);
+            if rest.len() > 1 {
+                return Err(format!("{}: too many arguments for {}", name, option));
+            }
+            Ok(Command::SetOption {
+                name,
+                flags,
+                option,
+                value: rest.pop(),
+            })
+        }
+        "bind-key" | "bind" => {
+            let (flags, mut rest) = parse_flags(args, "NT", &name)?;
+            if rest.is_empty() {
+                return Err(format!("{}: missing key", name));
+            }
+            let key = rest.remove(0);
+
+            // Arguments after the key are the bound commands; a literal `;`
+            // (written `\;`) separates them. A lone `{ }` block is a command
+            // list of its own.
+            let mut commands = Vec::new();
+            if ends_with_block && rest.len() == 1 {
+                commands = parse_commands(&rest[0])?;
+            } else {
+                let mut command_args = Vec::new();
+                for arg in rest.into_iter().chain(std::iter::once(String::from(";"))) {
+                    if arg == ";" {
+                        if !command_args.is_empty() {
+                            commands.push(parse_command(std::mem::take(&mut command_args))?);
+                        }
+                    } else {
+                        command_args.push(Token::Word(arg));
+                    }
+                }
+            }
+
+            Ok(Command::Bind {
+                name,
+                flags,
+                key,
+                commands,
+            })
+        }
+        "unbind-key" | "unbind" => {
+            let (flags, mut rest) = parse_flags(args, "T", &name)?;
+            if rest.is_empty() && !flags.has('a') {
+                return Err(format!("{}: missing key", name));
+            }
+            if rest.len() > 1 {
+                return Err(format!("{}: too many arguments", name));
+            }
+            Ok(Command::Unbind {
+                name,
+                flags,
+                key: rest.pop(),
+            })
+        }
+        "source-file" | "source" => {
+            let (flags, paths) = parse_flags(args, "t", &name)?;
+            if paths.is_empty() {
+                return Err(format!("{}: missing path", name));
+            }
+            Ok(Command::SourceFile { name, flags, paths })
+        }
+        "if-shell" | "if" => {
+            let (flags, mut rest) = parse_flags(args, "t", &name)?;
+            if rest.len() < 2 {
+                return Err(format!("{}: expected a condition and a command", name));
+            }
+            if rest.len() > 3 {
+                return Err(format!("{}: too many arguments", name));
+            }
+            let else_command = if rest.len() == 3 { rest.pop() } else { None };
+            let then_command = rest.pop().unwrap_or_default();
+            let condition = rest.pop().unwrap_or_default();
+            Ok(Command::IfShell {
+                name,
+                flags,
+                condition,
+                then_command,
+                else_command,
+            })
+        }
+        _ => Ok(Command::Other { name, args }),
+    }
+}
+
+/// Splits leading getopt-style flags from the positional arguments.
+/// `with_value` lists the flags that take an argument.
+fn parse_flags(
+    args: Vec<String>,
+    with_value: &str,
+    command: &str,
+) -> Result<(Flags, Vec<String>), String> {
+    let mut flags = Flags::default();
+    let mut args = args.into_iter().peekable();
+
+    while let Some(arg) = args.peek() {
+        if arg == "--" {
+            args.next();
+            break;
+        }
+        if !arg.starts_with('-') || arg.len() < 2 {
+            break;
+        }
+
+        let arg = args.next().unwrap_or_default();
+        for (i, flag) in arg[1..].char_indices() {
+            if with_value.contains(flag) {
+                // The value is the rest of this argument or the next one
+                let attached = &arg[1 + i + flag.len_utf8()..];
+                let value = if attached.is_empty() {
+                    args.next()
+                        .ok_or_else(|| format!("{}: -{} needs a value", command, flag))?
+                } else {
+                    attached.to_string()
+                };
+                flags.values.push((flag, value));
+                break;
+            }
+            flags.switches.push(flag);
+        }
+    }
+
+    Ok((flags, args.collect()))
+}
+
+/// Quotes an argument so tmux reads it back unchanged
+fn quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_-.:/@%+=,~^!*<>[]|".contains(c);
+
+    if !arg.is_empty() && arg.chars().all(is_safe) && !arg.starts_with('-') {
+        return arg.to_string();
+    }
+    if arg == ";" {
+        return String::from("\\;");
+    }
+    if arg.contains('\n') {
+        return format!("{{\n{}\n}}", arg);
+    }
+    if !arg.contains('\'') {
+        return format!("'{}'", arg);
+    }
+
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '\\' | '$') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.switches.is_empty() {
+            parts.push(format!("-{}", self.switches.iter().collect::<String>()));
+        }
+        for (flag, value) in &self.values {
+            parts.push(format!("-{} {}", flag, quote(value)));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl fmt::Display for Command {
+    /// Writes the command in canonical form, which parses back to the same
+    /// command
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        let push_flags = |parts: &mut Vec<String>, flags: &Flags| {
+            if !flags.is_empty() {
+                parts.push(flags.to_string());
+            }
+        };
+
+        match self {
+            Command::SetOption {
+                name,
+                flags,
+                option,
+                value,
+            } => {
+                parts.push(name.clone());
+                push_flags(&mut parts, flags);
+                parts.push(quote(option));
+                if let Some(value) = value {
+                    parts.push(quote(value));
+                }
+            }
+            Command::Bind {
+                name,
+                flags,
+                key,
+                commands,
+            } => {
+                parts.push(name.clone());
+                push_flags(&mut parts, flags);
+                parts.push(quote(key));
+                let bound: Vec<String> = commands.iter().map(|c| c.to_string()).collect();
+                if !bound.is_empty() {
+                    parts.push(bound.join(" \\; "));
+                }
+            }
+            Command::Unbind { name, flags, key } => {
+                parts.push(name.clone());
+                push_flags(&mut parts, flags);
+                if let Some(key) = key {
+                    parts.push(quote(key));
+                }
+            }
+            Command::SourceFile { name, flags, paths } => {
+                parts.push(name.clone());
+                push_flags(&mut parts, flags);
+                parts.extend(paths.iter().map(|path| quote(path)));
+            }
+            Command::IfShell {
+                name,
+                flags,
+                condition,
+                then_command,
+                else_command,
+            } => {
+                parts.push(name.clone());
+                push_flags(&mut parts, flags);
+                parts.push(quote(condition));
+                parts.push(quote(then_command));
+                if let Some(else_command) = else_command {
+                    parts.push(quote(else_command));
+                }
+            }
+            Command::Assignment { name, value } => {
+                parts.push(format!("{}={}", name, quote(value)));
+            }
+            Command::Other { name, args } => {
+                parts.push(quote(name));
+                parts.extend(args.iter().map(|arg| quote(arg)));
+            }
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl fmt::Display for ConfigFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line.raw)?;
+        }
+        if self.trailing_newline {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    const SAMPLE: &str = r##"# prefix
+unbind C-b
+set -g prefix C-a
+bind C-a send-prefix
+
+set -g mouse on   # mouse support
+setw -g mode-keys vi
+set-option -sg escape-time 10
+set -g status-right "#[fg=yellow]#{session_name} | %H:%M"
+set -ga terminal-overrides ',xterm-256color:Tc'
+
+bind r source-file ~/.tmux.conf \; display-message "reloaded"
+bind -T copy-mode-vi v send-keys -X begin-selection
+bind -n M-Left select-pane -L
+bind-key -r -N "resize left" H resize-pane -L 5
+unbind -a -T copy-mode
+
+is_vim="ps -o state= -o comm= -t '#{pane_tty}' | grep -iqE 'vim'"
+bind -n C-h if-shell "$is_vim" "send-keys C-h" "select-pane -L"
+
+%if "#{==:#{host},work}"
+source-file -q ~/.tmux/work.conf
+%elif "#{==:#{host},home}"
+set -g status-style bg=blue
+%else
+set -g status-style bg=green
+%endif
+%hidden THEME=dark
+
+set -g status-left \
+  "[#S] "
+bind x {
+  kill-pane
+}
+if -F '#{==:#{session_name},main}' { set -g status off }
+new-session -d; set -g base-index 1
+"##;
+
+    #[test]
+    fn round_trips_sample_exactly() {
+        let parsed = parse(SAMPLE);
+        assert_eq!(parsed.to_string(), SAMPLE);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    }
+
+    #[test]
+    fn round_trips_without_trailing_newline() {
+        let input = "set -g mouse on\n\n# done";
+        assert_eq!(parse(input).to_string(), input);
+        assert_eq!(parse("").to_string(), "");
+    }
+
+    #[test]
+    fn canonical_commands_parse_back_to_themselves() {
+        let texts = std::iter::once(SAMPLE.to_string())
+            .chain(fixtures().into_iter().map(|(_, contents)| contents));
+        for text in texts {
+            for (line, command) in parse(&text).commands() {
+                let rendered = command.to_string();
+                let reparsed = parse_commands(&rendered)
+                    .unwrap_or_else(|e| panic!("line {}: {}: {}", line, rendered, e));
+                assert_eq!(
+                    reparsed,
+                    vec![command.clone()],
+                    "line {}: {}",
+                    line,
+                    rendered
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parses_set_options() {
+        let parsed = parse("set-option -sg escape-time 10\nset -ga terminal-overrides ',a:Tc'\n");
+        let commands: Vec<&Command> = parsed.commands().map(|(_, c)| c).collect();
+
+        match commands[0] {
+            Command::SetOption {
+                flags,
+                option,
+                value,
+                ..
+            } => {
+                assert!(flags.has('s') && flags.has('g'));
+                assert_eq!(option, "escape-time");
+                assert_eq!(value.as_deref(), Some("10"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match commands[1] {
+            Command::SetOption { flags, value, .. } => {
+                assert!(flags.has('a'));
+                assert_eq!(value.as_deref(), Some(",a:Tc"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_bind_with_key_table_and_command_list() {
+        let parsed = parse(SAMPLE);
+        let binds: Vec<&Command> = parsed
+            .commands()
+            .map(|(_, c)| c)
+            .filter(|c| matches!(c, Command::Bind { .. }))
+            .collect();
+
+        match binds[1] {
+            Command::Bind { key, commands, .. } => {
+                assert_eq!(key, "r");
+                assert_eq!(commands.len(), 2);
+                assert!(
+                    matches!(&commands[0], Command::SourceFile { paths, .. } if paths == &["~/.tmux.conf"])
+                );
+                assert!(
+                    matches!(&commands[1], Command::Other { name, args } if name == "display-message" && args == &["reloaded"])
+                );
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match binds[2] {
+            Command::Bind { flags, key, .. } => {
+                assert_eq!(flags.value('T'), Some("copy-mode-vi"));
+                assert_eq!(key, "v");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match binds[4] {
+            Command::Bind { flags, key, .. } => {
+                assert!(flags.has('r'));
+                assert_eq!(flags.value('N'), Some("resize left"));
+                assert_eq!(key, "H");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn keeps_line_numbers_and_comments() {
+        let parsed = parse(SAMPLE);
+        let mouse = parsed
+            .lines
+            .iter()
+            .find(|line| line.raw.contains("mouse on"))
+            .unwrap();
+        assert_eq!(mouse.number, 6);
+        assert!(
+            matches!(&mouse.kind, LineKind::Commands { comment: Some(c), .. } if c == " mouse support")
+        );
+        assert_eq!(
+            parsed.lines[0].kind,
+            LineKind::Comment(String::from(" prefix"))
+        );
+
+        // Continuations and blocks count as one logical line
+        let status_left = parsed
+            .lines
+            .iter()
+            .find(|line| line.raw.starts_with("set -g status-left"))
+            .unwrap();
+        assert!(
+            matches!(&status_left.kind, LineKind::Commands { commands, .. }
+            if commands == &[Command::SetOption {
+                name: String::from("set"),
+                flags: Flags { switches: vec!['g'], values: Vec::new() },
+                option: String::from("status-left"),
+                value: Some(String::from("[#S] ")),
+            }])
+        );
+        let block = parsed
+            .lines
+            .iter()
+            .find(|line| line.raw.starts_with("bind x"))
+            .unwrap();
+        let next = parsed
+            .lines
+            .iter()
+            .find(|line| line.number > block.number)
+            .unwrap();
+        assert_eq!(next.number, block.number + 3);
+    }
+
+    #[test]
+    fn parses_conditionals_and_if_shell() {
+        let parsed = parse(SAMPLE);
+        let directives: Vec<&Directive> = parsed
+            .lines
+            .iter()
+            .filter_map(|line| match &line.kind {
+                LineKind::Directive(d) => Some(d),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            directives,
+            vec![
+                &Directive::If(String::from("#{==:#{host},work}")),
+                &Directive::Elif(String::from("#{==:#{host},home}")),
+                &Directive::Else,
+                &Directive::Endif,
+                &Directive::Hidden {
+                    name: String::from("THEME"),
+                    value: String::from("dark")
+                },
+            ]
+        );
+
+        let if_shell = parsed
+            .commands()
+            .map(|(_, c)| c)
+            .find(|c| matches!(c, Command::IfShell { .. }))
+            .unwrap();
+        assert_eq!(
+            if_shell,
+            &Command::IfShell {
+                name: String::from("if"),
+                flags: Flags {
+                    switches: vec!['F'],
+                    values: Vec::new()
+                },
+                condition: String::from("#{==:#{session_name},main}"),
+                then_command: String::from("set -g status off"),
+                else_command: None,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let parsed = parse("set -g\nbind\n%else\nset -g status-left \"oops\n%if 1\n");
+        let errors: Vec<(usize, &str)> = parsed
+            .errors
+            .iter()
+            .map(|e| (e.line, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (1, "set: missing option name"),
+                (2, "bind: missing key"),
+                (3, "%else without %if"),
+                (4, "unterminated double quote"),
+                (5, "%if without matching %endif"),
+            ]
+        );
+        // Invalid lines still round-trip
+        assert_eq!(
+            parsed.to_string(),
+            "set -g\nbind\n%else\nset -g status-left \"oops\n%if 1\n"
+        );
+    }
+
+    #[test]
+    fn handles_bare_formats_and_attached_separators() {
+        let text = "%if #{TMUX}\nset -g status-bg red\n%endif\nbind y set synchronize-panes\\; display 'on'\n";
+        let parsed = parse(text);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        assert_eq!(
+            parsed.lines[0].kind,
+            LineKind::Directive(Directive::If(String::from("#{TMUX}")))
+        );
+
+        match parsed.commands().map(|(_, c)| c).last() {
+            Some(Command::Bind { key, commands, .. }) => {
+                assert_eq!(key, "y");
+                assert_eq!(commands.len(), 2);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(parsed.to_string(), text);
+    }
+
+    #[test]
+    fn parses_commands_inside_bind_blocks() {
+        let text = "bind x { kill-pane; display hi }\nbind -n M-y {\n  # don't ask\n  kill-window\n  display 'bye'\n}\n";
+        let parsed = parse(text);
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+
+        let other = |name: &str, args: &[&str]| Command::Other {
+            name: name.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        };
+        let bound: Vec<&Vec<Command>> = parsed
+            .commands()
+            .filter_map(|(_, command)| match command {
+                Command::Bind { commands, .. } => Some(commands),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            bound,
+            vec![
+                &vec![other("kill-pane", &[]), other("display", &["hi"])],
+                &vec![other("kill-window", &[]), other("display", &["bye"])],
+            ]
+        );
+        assert_eq!(parsed.to_string(), text);
+    }
+
+    fn fixtures() -> Vec<(PathBuf, String)> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let mut fixtures: Vec<(PathBuf, String)> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let contents = fs::read_to_string(&path).unwrap();
+                (path, contents)
+            })
+            .collect();
+        fixtures.sort();
+        fixtures
+    }
+
+    #[test]
+    fn round_trips_fixtures() {
+        let fixtures = fixtures();
+        assert!(!fixtures.is_empty());
+        for (path, contents) in fixtures {
+            let parsed = parse(&contents);
+            assert!(
+                parsed.errors.is_empty(),
+                "{}: {:?}",
+                path.display(),
+                parsed.errors
+            );
+            assert_eq!(parsed.to_string(), contents, "{}", path.display());
+        }
+    }
+
+    /// Every config in the store at `$TMUCKS_HOME`, or the default store,
+    /// must round-trip. Run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "reads the local profile store"]
+    fn round_trips_store_configs() {
+        let store = match std::env::var_os("TMUCKS_HOME") {
+            Some(store) => PathBuf::from(store),
+            None => dirs::home_dir().unwrap().join(".config").join("tmucks"),
+        };
+
+        for entry in fs::read_dir(&store).unwrap().flatten() {
+            let path = entry.path();
+            if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("conf") {
+                continue;
+            }
+            let contents = fs::read_to_string(&path).unwrap();
+            assert_eq!(parse(&contents).to_string(), contents, "{}", path.display());
+        }
+    }
+}
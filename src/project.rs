@@ -0,0 +1,104 @@
+//! Per-project `.tmucks` files.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::Result;
+
+/// Name of the file that marks a project directory
+pub const PROJECT_FILE: &str = ".tmucks";
+
+/// A `.tmucks` file. If its first line, ignoring blanks and comments, is a
+/// single word, it names a saved config to use. Everything else is tmux
+/// config applied on top of it.
+///
+/// ```text
+/// work
+/// set -g status-style bg=colour52
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectFile {
+    /// Where the file is
+    pub path: PathBuf,
+    /// The saved config named on its first line, if any
+    pub profile: Option<String>,
+    /// The rest of the file, with the profile line left blank so line
+    /// numbers still match
+    pub overrides: String,
+}
+
+impl ProjectFile {
+    /// Looks for a `.tmucks` file in `dir` and then in each of its parents
+    pub fn find(dir: &Path) -> Result<Option<Self>> {
+        for dir in dir.ancestors() {
+            let path = dir.join(PROJECT_FILE);
+            if path.is_file() {
+                return Self::load(&path).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the `.tmucks` file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let (profile, overrides) = parse(&fs::read_to_string(path)?);
+        Ok(Self {
+            path: path.to_path_buf(),
+            profile,
+            overrides,
+        })
+    }
+
+    /// The project directory the file marks
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("/"))
+    }
+}
+
+fn parse(contents: &str) -> (Option<String>, String) {
+    let mut profile = None;
+    let mut lines: Vec<&str> = contents.lines().collect();
+
+    if let Some(first) = lines.iter_mut().find(|line| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    }) {
+        let word = first.trim();
+        // `NAME=value` and `%if` lines are tmux config, not names
+        if !word.contains(char::is_whitespace) && !word.contains('=') && !word.starts_with('%') {
+            profile = Some(word.to_string());
+            *first = "";
+        }
+    }
+
+    let mut overrides = lines.join("\n");
+    if contents.ends_with('\n') {
+        overrides.push('\n');
+    }
+    (profile, overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_word_names_the_profile() {
+        let (profile, overrides) = parse("# project\nwork\nset -g status-style bg=red\n");
+        assert_eq!(profile.as_deref(), Some("work"));
+        assert_eq!(overrides, "# project\n\nset -g status-style bg=red\n");
+    }
+
+    #[test]
+    fn config_only_files_have_no_profile() {
+        let contents = "set -g mouse on\nwork\n";
+        let (profile, overrides) = parse(contents);
+        assert_eq!(profile, None);
+        assert_eq!(overrides, contents);
+
+        let (profile, _) = parse("EDITOR=vim\n");
+        assert_eq!(profile, None);
+    }
+}
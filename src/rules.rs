@@ -0,0 +1,202 @@
+//! Rules picking the saved config to apply on a machine.
+
+use serde::Deserialize;
+use std::{env, fmt};
+
+use crate::template;
+
+/// A `[[rule]]` from the settings file picking the config `tmucks auto`
+/// applies. Every condition that is set has to hold; a rule without any
+/// matches everywhere.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Saved config to apply when the rule matches
+    pub profile: String,
+    /// Hostname pattern, where `*` matches any run of characters and `?` any
+    /// one character. Case is ignored.
+    pub hostname: Option<String>,
+    /// Whether this has to be, or not be, an SSH session
+    pub ssh: Option<bool>,
+    /// `$TERM` pattern, like `hostname` but case-sensitive
+    pub term: Option<String>,
+    /// Operating system as Rust names it: `linux`, `macos`, `freebsd`, ...
+    pub os: Option<String>,
+}
+
+/// What rules are matched against
+#[derive(Clone, Debug, PartialEq)]
+pub struct Environment {
+    /// This machine's hostname
+    pub hostname: Option<String>,
+    /// `$SSH_CONNECTION` is set
+    pub ssh: bool,
+    /// `$TERM`
+    pub term: Option<String>,
+    /// Operating system as Rust names it
+    pub os: String,
+}
+
+impl Environment {
+    /// The machine and terminal tmucks is running in
+    pub fn current() -> Self {
+        let set = |name| env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            hostname: template::hostname(),
+            ssh: set("SSH_CONNECTION").is_some(),
+            term: set("TERM"),
+            os: env::consts::OS.to_string(),
+        }
+    }
+}
+
+impl Rule {
+    /// Whether every condition the rule sets holds in `environment`
+    pub fn matches(&self, environment: &Environment) -> bool {
+        let pattern_matches = |pattern: &Option<String>, value: &Option<String>, ignore_case| {
+            pattern.as_ref().is_none_or(|pattern| {
+                value.as_ref().is_some_and(|value| {
+                    if ignore_case {
+                        glob_match(&pattern.to_lowercase(), &value.to_lowercase())
+                    } else {
+                        glob_match(pattern, value)
+                    }
+                })
+            })
+        };
+
+        pattern_matches(&self.hostname, &environment.hostname, true)
+            && self.ssh.is_none_or(|ssh| ssh == environment.ssh)
+            && pattern_matches(&self.term, &environment.term, false)
+            && self
+                .os
+                .as_ref()
+                .is_none_or(|os| os.eq_ignore_ascii_case(&environment.os))
+    }
+}
+
+impl fmt::Display for Rule {
+    /// The rule's conditions, as written in the settings file
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut conditions = Vec::new();
+        if let Some(hostname) = &self.hostname {
+            conditions.push(format!("hostname = {:?}", hostname));
+        }
+        if let Some(ssh) = self.ssh {
+            conditions.push(format!("ssh = {}", ssh));
+        }
+        if let Some(term) = &self.term {
+            conditions.push(format!("term = {:?}", term));
+        }
+        if let Some(os) = &self.os {
+            conditions.push(format!("os = {:?}", os));
+        }
+
+        if conditions.is_empty() {
+            write!(f, "fallback")
+        } else {
+            write!(f, "{}", conditions.join(", "))
+        }
+    }
+}
+
+/// The first of `rules` matching `environment`, with its position
+pub fn select<'a>(rules: &'a [Rule], environment: &Environment) -> Option<(usize, &'a Rule)> {
+    rules
+        .iter()
+        .enumerate()
+        .find(|(_, rule)| rule.matches(environment))
+}
+
+/// Matches `text` against a pattern where `*` matches any run of characters
+/// and `?` any one character
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and how much of the text it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` take one more character and retry
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(profile: &str) -> Rule {
+        Rule {
+            profile: profile.to_string(),
+            hostname: None,
+            ssh: None,
+            term: None,
+            os: None,
+        }
+    }
+
+    fn environment() -> Environment {
+        Environment {
+            hostname: Some("Build-07.example.com".to_string()),
+            ssh: true,
+            term: Some("xterm-256color".to_string()),
+            os: "linux".to_string(),
+        }
+    }
+
+    #[test]
+    fn globs_match_whole_text() {
+        assert!(glob_match("build-*", "build-07"));
+        assert!(glob_match("*.example.com", "build-07.example.com"));
+        assert!(glob_match("build-0?", "build-07"));
+        assert!(glob_match("*-*-*", "a-b-c"));
+        assert!(!glob_match("build-*", "rebuild-07"));
+        assert!(!glob_match("build-0?", "build-007"));
+    }
+
+    #[test]
+    fn every_condition_has_to_hold() {
+        let mut remote_build = rule("build.conf");
+        remote_build.hostname = Some("build-*".to_string());
+        remote_build.ssh = Some(true);
+        assert!(remote_build.matches(&environment()));
+
+        remote_build.os = Some("macos".to_string());
+        assert!(!remote_build.matches(&environment()));
+
+        let mut kitty = rule("kitty.conf");
+        kitty.term = Some("xterm-kitty".to_string());
+        assert!(!kitty.matches(&environment()));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let mut local = rule("local.conf");
+        local.ssh = Some(false);
+        let rules = vec![local, rule("fallback.conf"), rule("never.conf")];
+        let (index, selected) = select(&rules, &environment()).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(selected.profile, "fallback.conf");
+    }
+}
@@ -0,0 +1,611 @@
+//! Overlays that set a config's options on a single tmux session.
+
+use serde::{Deserialize, Serialize};
+use std::{env, fs, process::Output};
+
+use crate::error::{Error, Result};
+use crate::parser::{self, Command, Flags};
+use crate::tmux::Tmux;
+
+/// Session option recording what the overlay on a session changed
+const OVERLAY_OPTION: &str = "@tmucks-overlay";
+
+/// Switches of `set-option` that still make sense once the option is scoped
+/// to a session or window; `-g`, `-s` and the target are replaced
+const KEPT_SWITCHES: &str = "aFoqu";
+
+/// What put an overlay on a session
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayKind {
+    /// A `.tmucks` file, applied by `tmucks project`
+    Project,
+    /// A saved config, applied by `tmucks try`
+    Try,
+}
+
+/// Options set on one session on top of the global config, with the values
+/// they replaced. It is kept in the session itself, so it goes away with it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Overlay {
+    /// Whether a project file or `tmucks try` put it there
+    pub kind: OverlayKind,
+    /// The project directory or saved config the options came from
+    pub source: String,
+    digest: u64,
+    saved: Vec<SavedOption>,
+    /// The copy of the root key table the session was switched to
+    #[serde(default)]
+    key_table: Option<String>,
+}
+
+impl Overlay {
+    /// Whether this overlay is `configs` applied from `source`
+    pub fn is_from(&self, kind: OverlayKind, source: &str, configs: &[(String, String)]) -> bool {
+        self.kind == kind && self.source == source && self.digest == digest(configs)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct SavedOption {
+    /// Session or window id the option was set on
+    target: String,
+    window: bool,
+    option: String,
+    /// The target's own value before, or None if it used the global one
+    previous: Option<String>,
+}
+
+/// A config line that was not set on the session, and why
+#[derive(Clone, Debug)]
+pub struct LineReport {
+    /// The config or file the line is in
+    pub source: String,
+    /// 1-based line number
+    pub line: usize,
+    /// Why it was skipped, or tmux's complaint
+    pub message: String,
+}
+
+/// How putting an overlay on a session went
+#[derive(Debug, Default)]
+pub struct Applied {
+    /// Number of options and key bindings set
+    pub set: usize,
+    /// Lines that can't be scoped to a session, like key bindings
+    pub skipped: Vec<LineReport>,
+    /// Lines tmux refused or that could not be parsed
+    pub failed: Vec<LineReport>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scope {
+    Server,
+    Session,
+    Window,
+}
+
+/// The names of the server, session and window options tmux knows about
+struct OptionTables {
+    server: Vec<String>,
+    window: Vec<String>,
+}
+
+/// A running tmux session, to set options on without touching the others
+pub struct Session {
+    tmux: Tmux,
+    /// tmux's id for the session, like `$3`, which survives renames
+    pub id: String,
+    /// The session's name when it was found
+    pub name: String,
+}
+
+impl Session {
+    /// Finds the session called `name`, or the one tmucks is running inside
+    /// when `name` is None
+    pub fn find(tmux: &Tmux, name: Option<&str>) -> Result<Self> {
+        let session = |id: &str, session_name: &str| Self {
+            tmux: tmux.clone(),
+            id: id.to_string(),
+            name: session_name.to_string(),
+        };
+
+        let Some(name) = name else {
+            let pane = env::var("TMUX_PANE")
+                .ok()
+                .filter(|pane| !pane.is_empty() && env::var_os("TMUX").is_some())
+                .ok_or(Error::NoSession(None))?;
+            let output = tmux
+                .command()
+                .args(["display-message", "-p", "-t", &pane])
+                .arg("#{session_id} #{session_name}")
+                .output()?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return match stdout.trim_end_matches('\n').split_once(' ') {
+                Some((id, name)) if output.status.success() => Ok(session(id, name)),
+                _ => Err(Error::NoSession(None)),
+            };
+        };
+
+        let output = tmux
+            .command()
+            .args(["list-sessions", "-F", "#{session_id} #{session_name}"])
+            .output()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .find(|(_, session_name)| *session_name == name)
+            .map(|(id, session_name)| session(id, session_name))
+            .ok_or_else(|| Error::NoSession(Some(name.to_string())))
+    }
+
+    /// The overlay currently on the session, if any
+    pub fn overlay(&self) -> Result<Option<Overlay>> {
+        let record = self.show(&self.id, false, OVERLAY_OPTION)?;
+        Ok(record.and_then(|record| serde_json::from_str(&record).ok()))
+    }
+
+    /// Sets the options in each of `configs`, given as (name, text) pairs, on
+    /// this session only. Any overlay already on the session is reverted
+    /// first. Window options are set on each of the session's windows, and
+    /// root table key bindings (`bind -n`) go in a copy of the root table
+    /// that only this session uses. Other key bindings, server options and
+    /// other commands affect every session, so they are skipped.
+    pub fn apply(
+        &self,
+        kind: OverlayKind,
+        source: &str,
+        configs: &[(String, String)],
+    ) -> Result<Applied> {
+        self.revert()?;
+
+        let tables = self.option_tables()?;
+        let windows = self.windows()?;
+        let mut overlay = Overlay {
+            kind,
+            source: source.to_string(),
+            digest: digest(configs),
+            saved: Vec::new(),
+            key_table: None,
+        };
+        let mut applied = Applied::default();
+
+        for (config, text) in configs {
+            let parsed = parser::parse(text);
+            let report = |line: usize, message: String| LineReport {
+                source: config.clone(),
+                line,
+                message,
+            };
+
+            for error in &parsed.errors {
+                applied
+                    .failed
+                    .push(report(error.line, error.message.clone()));
+            }
+
+            for (line, command) in parsed.commands() {
+                let outcome = match command {
+                    Command::SetOption {
+                        name,
+                        flags,
+                        option,
+                        value,
+                    } => {
+                        let scope = if flags.has('s') {
+                            Scope::Server
+                        } else if flags.has('w')
+                            || flags.has('p')
+                            || name.starts_with("setw")
+                            || name == "set-window-option"
+                        {
+                            Scope::Window
+                        } else {
+                            tables.scope(option)
+                        };
+                        let targets = match scope {
+                            Scope::Server => {
+                                applied.skipped.push(report(
+                                    line,
+                                    format!(
+                                        "{} is a server option, shared by every session",
+                                        option
+                                    ),
+                                ));
+                                continue;
+                            }
+                            Scope::Session => vec![self.id.clone()],
+                            Scope::Window => windows.clone(),
+                        };
+                        let switches: Vec<char> = flags
+                            .switches
+                            .iter()
+                            .copied()
+                            .filter(|switch| KEPT_SWITCHES.contains(*switch))
+                            .collect();
+                        let window = scope == Scope::Window;
+                        self.set_saving(&mut overlay, &targets, window, &switches, option, value)
+                    }
+                    Command::Bind { flags, key, .. }
+                    | Command::Unbind {
+                        flags,
+                        key: Some(key),
+                        ..
+                    } if flags.has('n') || flags.value('T') == Some("root") => {
+                        let table = self.own_key_table(&mut overlay)?;
+                        match command {
+                            Command::Bind { commands, .. } => {
+                                self.bind(&table, flags, key, commands)
+                            }
+                            _ => self.unbind(&table, key),
+                        }
+                    }
+                    _ => {
+                        applied
+                            .skipped
+                            .push(report(line, skip_reason(command).to_string()));
+                        continue;
+                    }
+                };
+                match outcome {
+                    Ok(()) => applied.set += 1,
+                    Err(message) => applied.failed.push(report(line, message)),
+                }
+            }
+        }
+
+        let record = serde_json::to_string(&overlay).map_err(|e| Error::Tmux(e.to_string()))?;
+        self.set(&self.id, false, &[], OVERLAY_OPTION, Some(&record))
+            .map_err(Error::Tmux)?;
+        Ok(applied)
+    }
+
+    /// Sets an option on each of `targets`, first recording the value to go
+    /// back to. Returns tmux's complaint if it refused.
+    fn set_saving(
+        &self,
+        overlay: &mut Overlay,
+        targets: &[String],
+        window: bool,
+        switches: &[char],
+        option: &str,
+        value: &Option<String>,
+    ) -> std::result::Result<(), String> {
+        for target in targets {
+            // The value from before the overlay is the one to go back to
+            let known = overlay
+                .saved
+                .iter()
+                .any(|saved| &saved.target == target && saved.option == option);
+            if !known {
+                overlay.saved.push(SavedOption {
+                    target: target.clone(),
+                    window,
+                    option: option.to_string(),
+                    previous: self
+                        .show(target, window, option)
+                        .map_err(|e| e.to_string())?,
+                });
+            }
+            self.set(target, window, switches, option, value.as_deref())?;
+        }
+        Ok(())
+    }
+
+    /// Makes the session use its own copy of the root key table, so root
+    /// bindings can be changed for it alone, and returns the table's name
+    fn own_key_table(&self, overlay: &mut Overlay) -> Result<String> {
+        let table = format!("tmucks-{}", self.id.trim_start_matches('$'));
+        if overlay.key_table.is_some() {
+            return Ok(table);
+        }
+
+        // list-keys prints each binding as the command that makes it
+        let output = self
+            .tmux
+            .command()
+            .args(["list-keys", "-T", "root"])
+            .output()?;
+        let copy: String = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.replacen("-T root ", &format!("-T {} ", table), 1) + "\n")
+            .collect();
+        // Sourced into the live server, so written where nobody else can
+        // put a file of their own
+        let scratch_dir = tempfile::Builder::new().prefix("tmucks-keys-").tempdir()?;
+        let path = scratch_dir.path().join("keys.conf");
+        fs::write(&path, copy)?;
+        let output = self.tmux.command().arg("source-file").arg(&path).output()?;
+        check(&output).map_err(Error::Tmux)?;
+
+        self.set_saving(
+            overlay,
+            std::slice::from_ref(&self.id),
+            false,
+            &[],
+            "key-table",
+            &Some(table.clone()),
+        )
+        .map_err(Error::Tmux)?;
+        overlay.key_table = Some(table.clone());
+        Ok(table)
+    }
+
+    fn bind(
+        &self,
+        table: &str,
+        flags: &Flags,
+        key: &str,
+        commands: &[Command],
+    ) -> std::result::Result<(), String> {
+        let mut command = self.tmux.command();
+        command.args(["bind-key", "-T", table]);
+        if flags.has('r') {
+            command.arg("-r");
+        }
+        if let Some(note) = flags.value('N') {
+            command.args(["-N", note]);
+        }
+        command.args(["--", key]);
+        // A single argument is parsed as tmux commands
+        if !commands.is_empty() {
+            let bound: Vec<String> = commands.iter().map(ToString::to_string).collect();
+            command.arg(bound.join(" ; "));
+        }
+        check(&command.output().map_err(|e| e.to_string())?)
+    }
+
+    fn unbind(&self, table: &str, key: &str) -> std::result::Result<(), String> {
+        let output = self
+            .tmux
+            .command()
+            .args(["unbind-key", "-T", table, "--", key])
+            .output()
+            .map_err(|e| e.to_string())?;
+        check(&output)
+    }
+
+    /// Puts the session's options back as they were before its overlay,
+    /// returning the overlay, or None if there wasn't one
+    pub fn revert(&self) -> Result<Option<Overlay>> {
+        let Some(overlay) = self.overlay()? else {
+            return Ok(None);
+        };
+
+        for saved in overlay.saved.iter().rev() {
+            // Windows closed since then have nothing to put back
+            let _ = match &saved.previous {
+                Some(value) => {
+                    self.set(&saved.target, saved.window, &[], &saved.option, Some(value))
+                }
+                None => self.set(&saved.target, saved.window, &['u'], &saved.option, None),
+            };
+        }
+        if let Some(table) = &overlay.key_table {
+            let _ = self
+                .tmux
+                .command()
+                .args(["unbind-key", "-a", "-T", table])
+                .output();
+        }
+        self.set(&self.id, false, &['u'], OVERLAY_OPTION, None)
+            .map_err(Error::Tmux)?;
+        Ok(Some(overlay))
+    }
+
+    /// The target's own value for an option, or None if it uses the global one
+    fn show(&self, target: &str, window: bool, option: &str) -> Result<Option<String>> {
+        let mut command = self.tmux.command();
+        command.arg("show-options");
+        if window {
+            command.arg("-w");
+        }
+        let output = command.args(["-q", "-v", "-t", target, option]).output()?;
+
+        // An option set to "" still prints its newline
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .strip_suffix('\n')
+            .map(str::to_string)
+            .filter(|_| output.status.success()))
+    }
+
+    /// Runs `set-option` on one target, returning tmux's complaint if it fails
+    fn set(
+        &self,
+        target: &str,
+        window: bool,
+        switches: &[char],
+        option: &str,
+        value: Option<&str>,
+    ) -> std::result::Result<(), String> {
+        let mut command = self.tmux.command();
+        command.arg("set-option");
+        if window {
+            command.arg("-w");
+        }
+        if !switches.is_empty() {
+            command.arg(format!("-{}", switches.iter().collect::<String>()));
+        }
+        command.args(["-t", target, "--", option]);
+        if let Some(value) = value {
+            command.arg(value);
+        }
+
+        let output = command.output().map_err(|e| e.to_string())?;
+        check(&output)
+    }
+
+    /// Ids of the session's windows, like `@4`
+    fn windows(&self) -> Result<Vec<String>> {
+        let output = self
+            .tmux
+            .command()
+            .args(["list-windows", "-t", &self.id, "-F", "#{window_id}"])
+            .output()?;
+        check(&output).map_err(Error::Tmux)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn option_tables(&self) -> Result<OptionTables> {
+        let names = |scope: &str| -> Result<Vec<String>> {
+            let output = self.tmux.command().args(["show-options", scope]).output()?;
+            check(&output).map_err(Error::Tmux)?;
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.split([' ', '[']).next())
+                .map(str::to_string)
+                .collect())
+        };
+        Ok(OptionTables {
+            server: names("-s")?,
+            window: names("-gw")?,
+        })
+    }
+}
+
+impl OptionTables {
+    /// Which table tmux puts an option in when `set-option` isn't told.
+    /// User options like `@name` and anything unknown go on the session.
+    fn scope(&self, option: &str) -> Scope {
+        let name = option.split('[').next().unwrap_or(option);
+        if self.server.iter().any(|known| known == name) {
+            Scope::Server
+        } else if self.window.iter().any(|known| known == name) {
+            Scope::Window
+        } else {
+            Scope::Session
+        }
+    }
+}
+
+fn skip_reason(command: &Command) -> &'static str {
+    match command {
+        Command::Bind { .. } | Command::Unbind { .. } => {
+            "only root table key bindings (bind -n) can be kept to a single session"
+        }
+        Command::SourceFile { .. } => "other files are not sourced into a single session",
+        _ => "only options can be set on a single session",
+    }
+}
+
+fn check(output: &Output) -> std::result::Result<(), String> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// A hash of the configs an overlay was made from, kept in the session to
+/// tell whether they changed since. It is FNV-1a, which unlike std's hasher
+/// gives the same value whichever Rust built tmucks.
+fn digest(configs: &[(String, String)]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (name, contents) in configs {
+        // Each part ends in a NUL so ("ab", "c") and ("a", "bc") differ
+        for part in [name, contents] {
+            for byte in part.bytes().chain([0]) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A tmux server on its own socket, killed when dropped
+    struct TestServer {
+        tmux: Tmux,
+        _dir: tempfile::TempDir,
+    }
+
+    impl TestServer {
+        fn start() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            // Session::find has no socket to go by, so wrap tmux in a script
+            let program = dir.path().join("tmux");
+            fs::write(
+                &program,
+                format!(
+                    "#!/bin/sh\nexec tmux -S '{}' -f /dev/null \"$@\"\n",
+                    dir.path().join("sock").display()
+                ),
+            )
+            .unwrap();
+            fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+            let tmux = Tmux::new(program);
+            let output = tmux
+                .command()
+                .args(["new-session", "-d", "-s", "trial"])
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{:?}", output);
+            Self { tmux, _dir: dir }
+        }
+
+        fn run(&self, args: &[&str]) -> String {
+            let output = self.tmux.command().args(args).output().unwrap();
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            let _ = self.tmux.command().arg("kill-server").output();
+        }
+    }
+
+    #[test]
+    fn tries_block_bindings_on_one_session() {
+        let server = TestServer::start();
+        let session = Session::find(&server.tmux, Some("trial")).unwrap();
+        let config = "set -g status-left hello\nbind -n M-y { kill-pane; display bye }\n";
+        let configs = vec![(String::from("trial.conf"), String::from(config))];
+
+        let applied = session
+            .apply(OverlayKind::Try, "trial.conf", &configs)
+            .unwrap();
+        assert!(applied.failed.is_empty(), "{:?}", applied.failed);
+        assert!(applied.skipped.is_empty(), "{:?}", applied.skipped);
+        assert_eq!(applied.set, 2);
+
+        let table = format!("tmucks-{}", session.id.trim_start_matches('$'));
+        let keys = server.run(&["list-keys", "-T", &table, "M-y"]);
+        assert!(keys.contains("kill-pane"), "{}", keys);
+        assert!(keys.contains("display-message bye"), "{}", keys);
+        assert!(server.run(&["list-keys", "-T", "root", "M-y"]).is_empty());
+        assert_eq!(
+            server.run(&["show-options", "-v", "-t", &session.id, "status-left"]),
+            "hello\n"
+        );
+
+        assert!(session.revert().unwrap().is_some());
+        assert!(server.run(&["list-keys", "-T", &table]).is_empty());
+        assert_eq!(
+            server.run(&["show-options", "-v", "-t", &session.id, "status-left"]),
+            ""
+        );
+    }
+
+    #[test]
+    fn digests_stay_the_same_across_builds() {
+        let configs = |name: &str, contents: &str| vec![(name.to_string(), contents.to_string())];
+
+        // Pinned, since overlays recorded by older builds are compared to it
+        assert_eq!(
+            digest(&configs("work.conf", "set -g mouse on\n")),
+            0x4de3_34cb_8d82_c156
+        );
+        assert_ne!(digest(&configs("ab", "c")), digest(&configs("a", "bc")));
+    }
+}
@@ -0,0 +1,106 @@
+//! tmucks' settings file, and helpers for the paths it names.
+
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::ApplyMode;
+use crate::error::{Error, Result};
+use crate::rules::Rule;
+
+/// Name of the settings file in a profile store
+pub const SETTINGS_FILE: &str = "settings.toml";
+
+/// tmucks' own settings, read from `settings.toml` in the profile store, so
+/// a store shared between machines carries its rules along
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// tmux config file to manage instead of the detected one
+    pub target: Option<String>,
+    /// Whether `apply` copies profiles or symlinks them
+    pub apply_mode: Option<ApplyMode>,
+    /// How many backups of the tmux config to keep
+    pub backup_limit: Option<usize>,
+    /// `[[rule]]` tables picking the config `tmucks auto` applies, tried in
+    /// order
+    #[serde(rename = "rule")]
+    pub rules: Vec<Rule>,
+}
+
+impl Settings {
+    /// Reads the settings of the store at `store`. Missing settings, or a
+    /// store without a settings file, leave them all unset.
+    pub fn load(store: &Path) -> Result<Self> {
+        let path = store.join(SETTINGS_FILE);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        toml::from_str(&contents).map_err(|e| Error::InvalidFile {
+            path,
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Expands a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    } else if path == "~" {
+        if let Some(home) = dirs::home_dir() {
+            return home;
+        }
+    }
+    PathBuf::from(path)
+}
+
+/// Shortens a path under the home directory to start with `~`
+pub fn display_path(path: &Path) -> String {
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(rest) = path.strip_prefix(&home) {
+            return format!("~/{}", rest.display());
+        }
+    }
+    path.display().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rules_from_the_store() {
+        let store = tempfile::tempdir().unwrap();
+        fs::write(
+            store.path().join(SETTINGS_FILE),
+            "apply_mode = \"symlink\"\nbackup_limit = 5\n\n[[rule]]\nprofile = \"remote.conf\"\nssh = true\n",
+        )
+        .unwrap();
+
+        let settings = Settings::load(store.path()).unwrap();
+        assert_eq!(settings.apply_mode, Some(ApplyMode::Symlink));
+        assert_eq!(settings.backup_limit, Some(5));
+        assert_eq!(settings.rules.len(), 1);
+        assert_eq!(settings.rules[0].profile, "remote.conf");
+        assert_eq!(settings.rules[0].ssh, Some(true));
+    }
+
+    #[test]
+    fn store_without_settings_uses_defaults() {
+        let store = tempfile::tempdir().unwrap();
+
+        let settings = Settings::load(store.path()).unwrap();
+        assert!(settings.target.is_none());
+        assert!(settings.apply_mode.is_none());
+        assert!(settings.backup_limit.is_none());
+        assert!(settings.rules.is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Turning the live state of a tmux server into a config.
+
+use chrono::Local;
+use std::process::Command as TmuxCommand;
+
+use crate::error::{Error, Result};
+use crate::parser::{self, Command, Flags};
+use crate::tmux::{Server, ThrowawayServer, Tmux};
+
+/// The option tables a snapshot covers: the `show-options` flags that list
+/// each one, and the `set-option` flags that set it
+const OPTION_TABLES: [(&str, &str, &str); 3] = [
+    ("Server options", "-s", "-s"),
+    ("Session options", "-g", "-g"),
+    ("Window options", "-gw", "-gw"),
+];
+
+/// Writes a config reproducing the live state of `server`: its global
+/// options and its key bindings. Only what differs from tmux's defaults is
+/// written, found by asking a throwaway server started without a config.
+pub fn capture(tmux: &Tmux, server: &Server) -> Result<String> {
+    let defaults = ThrowawayServer::start(tmux)?;
+    let live = || tmux.command_for(server);
+
+    let mut config = format!(
+        "# Snapshot of the running tmux server, taken {}\n",
+        Local::now().format("%Y-%m-%d %H:%M")
+    );
+    config.push_str("# Only settings that differ from tmux's defaults are included\n");
+
+    for (title, show, set) in OPTION_TABLES {
+        let changes = option_changes(
+            set,
+            &query(live(), &["show-options", show])?,
+            &query(defaults.command(), &["show-options", show])?,
+        );
+        push_section(&mut config, title, changes);
+    }
+
+    let changes = key_changes(
+        &query(live(), &["list-keys"])?,
+        &query(defaults.command(), &["list-keys"])?,
+    );
+    push_section(&mut config, "Key bindings", changes);
+
+    Ok(config)
+}
+
+fn query(mut command: TmuxCommand, args: &[&str]) -> Result<String> {
+    let output = command.args(args).output()?;
+    if !output.status.success() {
+        return Err(Error::Tmux(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn push_section(config: &mut String, title: &str, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    config.push_str(&format!("\n# {}\n", title));
+    for line in lines {
+        config.push_str(&line);
+        config.push('\n');
+    }
+}
+
+/// `set-option` commands turning the `default` listing from `show-options`
+/// into the `live` one. Lines are `name value` with the value quoted as tmux
+/// reads it back, and array options have a line per index.
+fn option_changes(set: &str, live: &str, default: &str) -> Vec<String> {
+    let name = |line: &str| line.split(' ').next().unwrap_or(line).to_string();
+    let default: Vec<&str> = default.lines().collect();
+    let live: Vec<&str> = live.lines().collect();
+
+    let mut changes: Vec<String> = live
+        .iter()
+        .filter(|line| !default.contains(line))
+        .map(|line| format!("set {} {}", set, line))
+        .collect();
+
+    // Options left without a value, like a removed array index
+    let live_names: Vec<String> = live.iter().map(|line| name(line)).collect();
+    changes.extend(
+        default
+            .iter()
+            .map(|line| name(line))
+            .filter(|option| !live_names.contains(option))
+            .map(|option| format!("set {}u {}", set, option)),
+    );
+    changes
+}
+
+/// `unbind-key` and `bind-key` commands turning the `default` listing from
+/// `list-keys` into the `live` one. Bindings are compared in the parser's
+/// canonical form, since `list-keys` pads its columns to the longest key.
+fn key_changes(live: &str, default: &str) -> Vec<String> {
+    let live = bindings(live);
+    let default = bindings(default);
+
+    let unbinds = default
+        .iter()
+        .filter(|(key, _)| !live.iter().any(|(live_key, _)| live_key == key))
+        .map(|((table, key), _)| {
+            Command::Unbind {
+                name: "unbind-key".to_string(),
+                flags: Flags {
+                    switches: Vec::new(),
+                    values: vec![('T', table.clone())],
+                },
+                key: Some(key.clone()),
+            }
+            .to_string()
+        });
+    let binds = live
+        .iter()
+        .filter(|binding| !default.contains(binding))
+        .map(|(_, bind)| bind.clone());
+
+    unbinds.chain(binds).collect()
+}
+
+/// Each binding in `list-keys` output, by table and key, in canonical form
+fn bindings(listing: &str) -> Vec<((String, String), String)> {
+    parser::parse(listing)
+        .commands()
+        .filter_map(|(_, command)| match command {
+            Command::Bind { flags, key, .. } => {
+                let table = flags.value('T').unwrap_or("prefix").to_string();
+                Some(((table, key.clone()), command.to_string()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_differing_from_defaults_are_set() {
+        let default = "prefix C-b\nstatus-format[0] \"left\"\nstatus-format[1] \"panes\"\n";
+        let live = "prefix C-a\nstatus-format[0] \"left\"\n@theme dark\n";
+        assert_eq!(
+            option_changes("-g", live, default),
+            vec![
+                "set -g prefix C-a",
+                "set -g @theme dark",
+                "set -gu status-format[1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn key_changes_ignore_column_padding() {
+        let default = "\
+bind-key    -T prefix       c                    new-window
+bind-key    -T prefix       x                    confirm-before -p \"kill-pane #P? (y/n)\" kill-pane
+bind-key    -T root         MouseDown1Pane       select-pane -t = \\; send-keys -M
+";
+        let live = "\
+bind-key -T prefix c new-window
+bind-key -T prefix r source-file /home/me/.tmux.conf
+bind-key -T prefix x kill-pane
+";
+        assert_eq!(
+            key_changes(live, default),
+            vec![
+                "unbind-key -T root MouseDown1Pane",
+                "bind-key -T prefix r source-file /home/me/.tmux.conf",
+                "bind-key -T prefix x kill-pane",
+            ]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Template variables in saved configs.
+
+use std::{collections::BTreeMap, env, ffi::CStr, fs, path::Path};
+
+use crate::error::{Error, Result};
+
+/// Values for the `{{name}}` placeholders in saved configs
+pub type Variables = BTreeMap<String, String>;
+
+/// Environment variables starting with this set template variables, so
+/// `TMUCKS_VAR_ACCENT_COLOR` sets `{{accent_color}}`
+pub const ENV_PREFIX: &str = "TMUCKS_VAR_";
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Splits `text` into literal text and placeholders. A placeholder is a name
+/// made of letters, digits, `_` and `-` between `{{` and `}}`, with optional
+/// spaces inside the braces. Braces around anything else are left alone.
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut search = 0;
+
+    while let Some(open) = text[search..].find("{{").map(|i| search + i) {
+        let Some(close) = text[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            break;
+        };
+        let name = text[open + 2..close].trim();
+        if is_name(name) {
+            if start < open {
+                segments.push(Segment::Text(&text[start..open]));
+            }
+            segments.push(Segment::Placeholder(name));
+            start = close + 2;
+            search = start;
+        } else {
+            search = open + 1;
+        }
+    }
+
+    if start < text.len() {
+        segments.push(Segment::Text(&text[start..]));
+    }
+    segments
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Names of the placeholders in `text`, in the order they first appear
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in segments(text) {
+        if let Segment::Placeholder(name) = segment {
+            if !names.iter().any(|known| known == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Fills in every placeholder in `text`. If any have no value, nothing is
+/// rendered and their names are returned instead.
+pub fn render(text: &str, variables: &Variables) -> std::result::Result<String, Vec<String>> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut missing: Vec<String> = Vec::new();
+
+    for segment in segments(text) {
+        match segment {
+            Segment::Text(text) => rendered.push_str(text),
+            Segment::Placeholder(name) => match variables.get(name) {
+                Some(value) => rendered.push_str(value),
+                None => {
+                    if !missing.iter().any(|known| known == name) {
+                        missing.push(name.to_string());
+                    }
+                }
+            },
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(rendered)
+    } else {
+        Err(missing)
+    }
+}
+
+/// Variables every config can use without setting them: `hostname` and
+/// `user`
+pub fn builtin() -> Variables {
+    let mut variables = Variables::new();
+    if let Some(hostname) = hostname() {
+        variables.insert("hostname".to_string(), hostname);
+    }
+    if let Some(user) = env::var("USER").ok().filter(|user| !user.is_empty()) {
+        variables.insert("user".to_string(), user);
+    }
+    variables
+}
+
+/// Variables set with `TMUCKS_VAR_<NAME>` environment variables, with the
+/// name lowercased
+pub fn from_env() -> Variables {
+    env::vars()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(ENV_PREFIX)?;
+            (!name.is_empty()).then(|| (name.to_lowercase(), value))
+        })
+        .collect()
+}
+
+/// Reads a TOML file of `name = value` pairs. Numbers and booleans are used
+/// as written. A missing file sets nothing.
+pub fn load(path: &Path) -> Result<Variables> {
+    if !path.exists() {
+        return Ok(Variables::new());
+    }
+
+    let invalid = |message: String| Error::InvalidFile {
+        path: path.to_path_buf(),
+        message,
+    };
+    let contents = fs::read_to_string(path)?;
+    let table: toml::Table = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+
+    table
+        .into_iter()
+        .map(|(name, value)| match value {
+            toml::Value::String(value) => Ok((name, value)),
+            toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                Ok((name, value.to_string()))
+            }
+            _ => Err(invalid(format!(
+                "variable '{}' must be a string, number or boolean",
+                name
+            ))),
+        })
+        .collect()
+}
+
+/// This machine's hostname, as `gethostname` reports it
+pub fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: gethostname writes at most buffer.len() bytes into the buffer
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let hostname = CStr::from_bytes_until_nul(&buffer).ok()?;
+    Some(hostname.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&str, &str)]) -> Variables {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let text = "set -g prefix {{prefix}}\nset -g status-style bg={{ accent_color }}\n";
+        let rendered = render(
+            text,
+            &variables(&[("prefix", "C-a"), ("accent_color", "blue")]),
+        );
+        assert_eq!(
+            rendered.unwrap(),
+            "set -g prefix C-a\nset -g status-style bg=blue\n"
+        );
+    }
+
+    #[test]
+    fn reports_missing_variables_once_in_order() {
+        let text = "{{b}} {{a}} {{b}} {{c}}";
+        let missing = render(text, &variables(&[("a", "1")])).unwrap_err();
+        assert_eq!(missing, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn leaves_other_braces_alone() {
+        let text = "bind x if -F '#{==:{{ not a name }},}' {{{name}}} {{";
+        assert_eq!(placeholders(text), vec!["name"]);
+        let rendered = render(text, &variables(&[("name", "v")])).unwrap();
+        assert_eq!(rendered, "bind x if -F '#{==:{{ not a name }},}' {v} {{");
+    }
+}
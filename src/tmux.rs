@@ -0,0 +1,390 @@
+//! Running tmux: finding and reloading servers, and trying configs in a
+//! throwaway server.
+
+use std::{
+    env, fmt, fs, io,
+    os::unix::{fs::FileTypeExt, net::UnixStream},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use tempfile::TempDir;
+
+use crate::check::{self, Issue};
+use crate::error::{Error, Result};
+
+/// What happened when the running tmux server was asked to source the config
+#[derive(Debug)]
+pub enum Reload {
+    /// tmux sourced the config without complaint
+    Reloaded,
+    /// There is no tmux binary to run
+    NotInstalled,
+    /// tmux is installed but no server is running, so there was nothing to
+    /// reload
+    NoServer,
+    /// The server sourced the config but reported errors
+    ConfigErrors(Vec<Issue>),
+}
+
+/// How reloading went on each server that was asked to
+pub type Reloads = Vec<(Server, Reload)>;
+
+/// A tmux server to reload
+#[derive(Clone, Debug, PartialEq)]
+pub enum Server {
+    /// Whichever server a plain `tmux` command talks to
+    Default,
+    /// The server listening on this socket
+    Socket(PathBuf),
+}
+
+impl fmt::Display for Server {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Server::Default => write!(f, "default server"),
+            Server::Socket(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Which tmux servers get reloaded after the config changes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ReloadTargets {
+    /// Only the default server
+    #[default]
+    Default,
+    /// These sockets
+    Sockets(Vec<PathBuf>),
+    /// Every live socket found by `find_sockets`
+    All,
+}
+
+impl ReloadTargets {
+    /// The servers to reload. With `All`, the socket directory is searched
+    /// now.
+    pub fn servers(&self) -> Vec<Server> {
+        match self {
+            ReloadTargets::Default => vec![Server::Default],
+            ReloadTargets::Sockets(sockets) => {
+                sockets.iter().cloned().map(Server::Socket).collect()
+            }
+            ReloadTargets::All => find_sockets().into_iter().map(Server::Socket).collect(),
+        }
+    }
+}
+
+/// The directory tmux keeps its sockets in, `$TMUX_TMPDIR/tmux-$UID` or
+/// `/tmp/tmux-$UID`
+pub fn socket_dir() -> PathBuf {
+    let base = env::var_os("TMUX_TMPDIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+    base.join(format!("tmux-{}", uid()))
+}
+
+/// The socket a plain `tmux` command connects to
+pub fn default_socket() -> PathBuf {
+    match env::var("TMUX") {
+        Ok(value) if !value.is_empty() => {
+            PathBuf::from(value.split(',').next().unwrap_or_default())
+        }
+        _ => socket_dir().join("default"),
+    }
+}
+
+/// Turns a `--socket` argument into a socket path. Bare names are looked up
+/// in the socket directory like `tmux -L`, anything with a slash is a path
+/// like `tmux -S`.
+pub fn resolve_socket(socket: &str) -> PathBuf {
+    if socket.contains('/') {
+        crate::settings::expand_home(socket)
+    } else {
+        socket_dir().join(socket)
+    }
+}
+
+/// Finds sockets with a tmux server listening on them, in `/tmp/tmux-$UID/`
+/// and `$TMUX_TMPDIR/tmux-$UID/`. Stale sockets left by servers that exited
+/// are skipped.
+pub fn find_sockets() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from(format!("/tmp/tmux-{}", uid()))];
+    let tmpdir_sockets = socket_dir();
+    if !dirs.contains(&tmpdir_sockets) {
+        dirs.push(tmpdir_sockets);
+    }
+
+    let mut sockets: Vec<PathBuf> = dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_socket()))
+        .map(|entry| entry.path())
+        .filter(|path| UnixStream::connect(path).is_ok())
+        .collect();
+    sockets.sort();
+    sockets
+}
+
+fn uid() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+/// The tmux binary tmucks runs, `tmux` from `$PATH` unless set otherwise
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tmux {
+    program: PathBuf,
+}
+
+impl Default for Tmux {
+    fn default() -> Self {
+        Self::new("tmux")
+    }
+}
+
+impl Tmux {
+    /// Runs `program` for every tmux command, like `/usr/local/bin/tmux`.
+    /// It is looked up in `$PATH` when it has no slash. `Tmux::default()`
+    /// runs `tmux`.
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+
+    /// The tmux binary this runs
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// A command running this tmux, to add arguments to
+    pub fn command(&self) -> Command {
+        Command::new(&self.program)
+    }
+
+    /// A command running this tmux against `server`
+    pub fn command_for(&self, server: &Server) -> Command {
+        let mut command = self.command();
+        if let Server::Socket(socket) = server {
+            command.arg("-S").arg(socket);
+        }
+        command
+    }
+
+    /// Checks whether the tmux binary can be run
+    pub fn is_installed(&self) -> bool {
+        self.command()
+            .arg("-V")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    /// The installed tmux version, like `3.4` from `tmux 3.4`
+    pub fn version(&self) -> Option<String> {
+        let output = self.command().arg("-V").output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let version = stdout.trim();
+        Some(version.strip_prefix("tmux ").unwrap_or(version).to_string())
+    }
+
+    /// Sources the config at `path` into each of `servers`
+    pub fn reload_all(&self, path: &Path, servers: &[Server]) -> Reloads {
+        servers
+            .iter()
+            .map(|server| (server.clone(), self.reload(path, server)))
+            .collect()
+    }
+
+    /// Sources the config at `path` into a running tmux server
+    pub fn reload(&self, path: &Path, server: &Server) -> Reload {
+        let output = match self
+            .command_for(server)
+            .arg("source-file")
+            .arg(path)
+            .output()
+        {
+            Ok(output) => output,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Reload::NotInstalled,
+            Err(error) => {
+                return Reload::ConfigErrors(vec![check::unlocated_issue(error.to_string())]);
+            }
+        };
+        if output.status.success() {
+            return Reload::Reloaded;
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("no server running on") || stderr.contains("error connecting to") {
+            return Reload::NoServer;
+        }
+
+        let errors = parse_source_errors(&combined_output(&output), path);
+        Reload::ConfigErrors(check::locate_errors(path, errors, self))
+    }
+}
+
+/// Splits the output of `tmux source-file` into messages, attaching the line
+/// number when tmux reported one as `<path>:<line>: <message>`
+pub fn parse_source_errors(output: &str, path: &Path) -> Vec<(Option<usize>, String)> {
+    let prefix = format!("{}:", path.display());
+
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let located = line.strip_prefix(&prefix).and_then(|rest| {
+                let (number, message) = rest.split_once(':')?;
+                Some((number.trim().parse().ok()?, message.trim().to_string()))
+            });
+            match located {
+                Some((number, message)) => (Some(number), message),
+                None => (None, line.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// A private tmux server on its own socket, used to try out configs without
+/// touching the user's sessions. The server is killed when this is dropped.
+pub struct ThrowawayServer {
+    tmux: Tmux,
+    socket: PathBuf,
+    /// Private to this user, so nobody else can swap the socket or the
+    /// files sourced from it
+    scratch_dir: TempDir,
+}
+
+impl ThrowawayServer {
+    /// Starts a server without any config, so it has tmux's defaults
+    pub fn start(tmux: &Tmux) -> Result<Self> {
+        let scratch_dir = tempfile::Builder::new().prefix("tmucks-check-").tempdir()?;
+        let socket = scratch_dir.path().join("tmux.sock");
+
+        let server = Self {
+            tmux: tmux.clone(),
+            socket,
+            scratch_dir,
+        };
+        let output = server
+            .command()
+            .args(["-f", "/dev/null", "new-session", "-d", "-s", "tmucks-check"])
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::Tmux(format!(
+                "Could not start a tmux server for checking: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(server)
+    }
+
+    /// Sources a file into the throwaway server, returning whether it
+    /// succeeded and everything tmux printed
+    pub fn source(&self, path: &Path) -> Result<(bool, String)> {
+        let output = self.command().arg("source-file").arg(path).output()?;
+        Ok((output.status.success(), combined_output(&output)))
+    }
+
+    /// Sources a snippet of config text, written to a scratch file first
+    pub fn source_text(&self, text: &str) -> Result<(bool, String)> {
+        let path = self.scratch_dir.path().join("snippet.conf");
+        fs::write(&path, text)?;
+        self.source(&path)
+    }
+
+    /// A command running tmux against this server
+    pub fn command(&self) -> Command {
+        let mut command = self.tmux.command();
+        command.arg("-S").arg(&self.socket).env_remove("TMUX");
+        command
+    }
+}
+
+impl Drop for ThrowawayServer {
+    fn drop(&mut self) {
+        // The scratch directory goes once the server is gone
+        let _ = self.command().arg("kill-server").output();
+    }
+}
+
+fn combined_output(output: &Output) -> String {
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_errors_are_split_by_line() {
+        let path = Path::new("/home/me/.tmux.conf");
+        let output = "\
+/home/me/.tmux.conf:2: invalid option: bogus-option
+/home/me/.tmux.conf:7: unknown command: frobnicate
+
+";
+        assert_eq!(
+            parse_source_errors(output, path),
+            vec![
+                (Some(2), String::from("invalid option: bogus-option")),
+                (Some(7), String::from("unknown command: frobnicate")),
+            ]
+        );
+    }
+
+    #[test]
+    fn source_errors_keep_colons_in_messages() {
+        let path = Path::new("/tmp/a.conf");
+        assert_eq!(
+            parse_source_errors("/tmp/a.conf:3: bad value: C-a: not a key\n", path),
+            vec![(Some(3), String::from("bad value: C-a: not a key"))]
+        );
+    }
+
+    #[test]
+    fn source_errors_without_a_line_are_kept_whole() {
+        let path = Path::new("/tmp/a.conf");
+        let output = "/tmp/a.conf: No such file or directory\n\
+                      no server running on /tmp/tmux-1000/default\n\
+                      /tmp/other.conf:4: unknown command: foo\n";
+        assert_eq!(
+            parse_source_errors(output, path),
+            vec![
+                (None, String::from("/tmp/a.conf: No such file or directory")),
+                (
+                    None,
+                    String::from("no server running on /tmp/tmux-1000/default")
+                ),
+                (
+                    None,
+                    String::from("/tmp/other.conf:4: unknown command: foo")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn socket_names_are_looked_up_and_paths_kept() {
+        // Like tmux -L
+        assert_eq!(resolve_socket("work"), socket_dir().join("work"));
+        // Like tmux -S
+        assert_eq!(
+            resolve_socket("/run/tmux/work.sock"),
+            PathBuf::from("/run/tmux/work.sock")
+        );
+        assert_eq!(resolve_socket("./work"), PathBuf::from("./work"));
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(resolve_socket("~/sockets/work"), home.join("sockets/work"));
+        }
+    }
+}
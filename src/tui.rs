@@ -1,4 +1,6 @@
 use crate::app::{App, InputMode};
+use crate::editor;
+use crate::highlight::highlight_conf;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
@@ -12,28 +14,26 @@ use ratatui::{
     widgets::{Block, BorderType, Borders, Clear, List, ListItem, Padding, Paragraph, Wrap},
     Frame, Terminal,
 };
+use std::fs;
 use std::io;
 use std::time::Duration;
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+use tmucks::config::ConfigManager;
+use tmucks::diff::{DiffKind, DiffLine};
+use tmucks::error::Result;
+
+pub fn run(config_manager: ConfigManager) -> Result<()> {
     // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    enter_terminal()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run
-    let mut app = App::new()?;
+    let mut app = App::new(config_manager)?;
     let res = run_app(&mut terminal, &mut app);
 
     // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    leave_terminal()?;
     terminal.show_cursor()?;
 
     if let Err(err) = res {
@@ -43,6 +43,41 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Switches to raw mode and the alternate screen
+fn enter_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+}
+
+/// Gives the terminal back as it was before `enter_terminal`
+fn leave_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
+}
+
+/// Hands the terminal over to the user's editor for the selected config,
+/// then takes it back and redraws from scratch
+fn edit_selected<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+) -> Result<()> {
+    let Some(config_name) = app.selected_config() else {
+        return Ok(());
+    };
+    let config_path = app.config_manager.config_path(&config_name)?;
+    let before = fs::read(&config_path)?;
+
+    leave_terminal()?;
+    terminal.show_cursor()?;
+    let edited = editor::edit(&config_path);
+    enter_terminal()?;
+    terminal.clear()?;
+    edited?;
+
+    let changed = fs::read(&config_path)? != before;
+    app.finish_edit(&config_name, changed)
+}
+
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
@@ -57,20 +92,25 @@ fn run_app<B: ratatui::backend::Backend>(
                 match app.input_mode {
                     InputMode::Normal => match key.code {
                         KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Char('/') => app.start_search(),
+                        KeyCode::Esc if !app.search_query.is_empty() => app.clear_search(),
                         KeyCode::Char('j') => app.next(),
                         KeyCode::Char('k') => app.previous(),
                         KeyCode::Down => app.next(),
                         KeyCode::Up => app.previous(),
+                        KeyCode::Char('J') => app.scroll_preview_down(),
+                        KeyCode::Char('K') => app.scroll_preview_up(),
                         KeyCode::Enter => {
-                            if let Err(e) = app.apply_config() {
+                            if let Err(e) = app.apply_config(false) {
                                 app.set_status_message(format!("- error: {}", e));
                             }
                         }
-                        KeyCode::Char('d') => {
-                            if let Err(e) = app.delete_config() {
+                        KeyCode::Char('F') => {
+                            if let Err(e) = app.apply_config(true) {
                                 app.set_status_message(format!("- error: {}", e));
                             }
                         }
+                        KeyCode::Char('d') => app.start_delete(),
                         KeyCode::Char('s') => {
                             app.input_mode = InputMode::Saving;
                             app.input_buffer.clear();
@@ -78,6 +118,44 @@ fn run_app<B: ratatui::backend::Backend>(
                         KeyCode::Char('u') => {
                             app.start_update_mode();
                         }
+                        KeyCode::Char('e') => {
+                            if let Err(e) = edit_selected(terminal, app) {
+                                app.set_status_message(format!("- error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('r') => app.start_name_prompt(InputMode::Renaming),
+                        KeyCode::Char('c') => app.start_name_prompt(InputMode::Copying),
+                        KeyCode::Char('v') => {
+                            if let Err(e) = app.open_diff() {
+                                app.set_status_message(format!("- error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('z') => {
+                            if let Err(e) = app.undo_apply() {
+                                app.set_status_message(format!("- error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('b') => {
+                            if let Err(e) = app.open_backups() {
+                                app.set_status_message(format!("- error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('t') => app.open_servers(),
+                        _ => {}
+                    },
+                    InputMode::Searching => match key.code {
+                        KeyCode::Enter => app.input_mode = InputMode::Normal,
+                        KeyCode::Esc => app.clear_search(),
+                        KeyCode::Down => app.next(),
+                        KeyCode::Up => app.previous(),
+                        KeyCode::Char(c) => {
+                            app.search_query.push(c);
+                            app.update_search();
+                        }
+                        KeyCode::Backspace => {
+                            app.search_query.pop();
+                            app.update_search();
+                        }
                         _ => {}
                     },
                     InputMode::Saving => match key.code {
@@ -94,11 +172,54 @@ fn run_app<B: ratatui::backend::Backend>(
                                 } else {
                                     format!("{}.conf", app.input_buffer)
                                 };
+                                // Saving over an existing name asks to update it
+                                app.input_mode = InputMode::Normal;
+                                app.input_buffer.clear();
                                 if let Err(e) = app.save_current_config(&name) {
                                     app.set_status_message(format!("- error: {}", e));
                                 }
+                            }
+                        }
+                        KeyCode::Esc => {
+                            app.input_mode = InputMode::Normal;
+                            app.input_buffer.clear();
+                            app.status_message = app.default_status_message.clone();
+                        }
+                        KeyCode::Char(c) => {
+                            app.input_buffer.push(c);
+                        }
+                        KeyCode::Backspace => {
+                            app.input_buffer.pop();
+                        }
+                        _ => {}
+                    },
+                    InputMode::Renaming | InputMode::Copying => match key.code {
+                        KeyCode::Enter => {
+                            if app.input_buffer.trim().is_empty() {
+                                app.set_status_message(String::from(
+                                    "- error: name cannot be empty",
+                                ));
                                 app.input_mode = InputMode::Normal;
                                 app.input_buffer.clear();
+                            } else {
+                                let name = if app.input_buffer.ends_with(".conf") {
+                                    app.input_buffer.clone()
+                                } else {
+                                    format!("{}.conf", app.input_buffer)
+                                };
+                                match app.finish_name_prompt(&name) {
+                                    // Taken, wait for enter again to overwrite
+                                    Ok(false) => {}
+                                    Ok(true) => {
+                                        app.input_mode = InputMode::Normal;
+                                        app.input_buffer.clear();
+                                    }
+                                    Err(e) => {
+                                        app.set_status_message(format!("- error: {}", e));
+                                        app.input_mode = InputMode::Normal;
+                                        app.input_buffer.clear();
+                                    }
+                                }
                             }
                         }
                         KeyCode::Esc => {
@@ -108,9 +229,11 @@ fn run_app<B: ratatui::backend::Backend>(
                         }
                         KeyCode::Char(c) => {
                             app.input_buffer.push(c);
+                            app.overwrite_pending = false;
                         }
                         KeyCode::Backspace => {
                             app.input_buffer.pop();
+                            app.overwrite_pending = false;
                         }
                         _ => {}
                     },
@@ -123,6 +246,67 @@ fn run_app<B: ratatui::backend::Backend>(
                         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
                             app.cancel_update();
                         }
+                        KeyCode::Char('j') | KeyCode::Down => app.scroll_diff_down(),
+                        KeyCode::Char('k') | KeyCode::Up => app.scroll_diff_up(),
+                        _ => {}
+                    },
+                    InputMode::DeleteConfirm => match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => {
+                            if let Err(e) = app.delete_config() {
+                                app.set_status_message(format!("- error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            app.input_mode = InputMode::Normal;
+                        }
+                        _ => {}
+                    },
+                    InputMode::ReapplyConfirm => match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => {
+                            if let Err(e) = app.reapply_config() {
+                                app.set_status_message(format!("- error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            app.input_mode = InputMode::Normal;
+                            app.status_message = app.default_status_message.clone();
+                        }
+                        _ => {}
+                    },
+                    InputMode::Diff => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.scroll_diff_down(),
+                        KeyCode::Char('k') | KeyCode::Up => app.scroll_diff_up(),
+                        KeyCode::Char('v') | KeyCode::Char('q') | KeyCode::Esc => {
+                            app.close_diff();
+                        }
+                        _ => {}
+                    },
+                    InputMode::Backups => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.next_backup(),
+                        KeyCode::Char('k') | KeyCode::Up => app.previous_backup(),
+                        KeyCode::Enter => {
+                            if let Err(e) = app.restore_backup() {
+                                app.set_status_message(format!("- error: {}", e));
+                            }
+                        }
+                        KeyCode::Char('b') | KeyCode::Char('q') | KeyCode::Esc => {
+                            app.close_backups();
+                        }
+                        _ => {}
+                    },
+                    InputMode::Servers => match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.next_server(),
+                        KeyCode::Char('k') | KeyCode::Up => app.previous_server(),
+                        KeyCode::Char(' ') => app.toggle_server(),
+                        KeyCode::Enter | KeyCode::Char('t') | KeyCode::Char('q') | KeyCode::Esc => {
+                            app.close_servers();
+                        }
+                        _ => {}
+                    },
+                    InputMode::ReloadErrors => match key.code {
+                        KeyCode::Enter | KeyCode::Char('q') | KeyCode::Esc => {
+                            app.close_reload_errors();
+                        }
                         _ => {}
                     },
                 }
@@ -148,7 +332,15 @@ fn ui(f: &mut Frame, app: &mut App) {
     render_header(f, app, chunks[0]);
 
     // Main content area
-    render_main_content(f, app, chunks[1]);
+    if app.input_mode == InputMode::Backups {
+        render_backups(f, app, chunks[1]);
+    } else if app.input_mode == InputMode::Diff {
+        render_diff(f, app, chunks[1]);
+    } else if app.input_mode == InputMode::Servers {
+        render_servers(f, app, chunks[1]);
+    } else {
+        render_main_content(f, app, chunks[1]);
+    }
 
     // Footer status bar
     render_footer(f, app, chunks[2]);
@@ -156,6 +348,10 @@ fn ui(f: &mut Frame, app: &mut App) {
     // Confirmation popup (rendered on top of everything)
     if app.input_mode == InputMode::UpdateConfirm {
         render_update_popup(f, app);
+    } else if app.input_mode == InputMode::DeleteConfirm {
+        render_delete_popup(f, app);
+    } else if app.input_mode == InputMode::ReloadErrors {
+        render_reload_errors_popup(f, app);
     }
 }
 
@@ -165,13 +361,23 @@ fn render_header(f: &mut Frame, app: &mut App, area: Rect) {
         .constraints([Constraint::Min(40), Constraint::Length(30)])
         .split(area);
 
-    // Title
-    let title = Paragraph::new("tmux config manager")
-        .style(
+    // Title and active store
+    let title_text = vec![
+        Line::from(Span::styled(
+            "tmux config manager",
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
-        )
+        )),
+        Line::from(vec![
+            Span::styled("store: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                app.config_manager.store_display(),
+                Style::default().fg(Color::Green),
+            ),
+        ]),
+    ];
+    let title = Paragraph::new(title_text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
@@ -194,12 +400,20 @@ fn render_header(f: &mut Frame, app: &mut App, area: Rect) {
             Style::default().fg(Color::Red),
         ))]
     } else {
-        let selected = app.list_state.selected().unwrap_or(0) + 1;
+        let selected = app.list_state.selected().map_or(0, |i| i + 1);
         vec![
             Line::from(vec![
                 Span::styled("configs: ", Style::default().fg(Color::Gray)),
                 Span::styled(
-                    format!("{}", app.config_manager.configs.len()),
+                    if app.listed.len() == app.config_manager.configs.len() {
+                        format!("{}", app.config_manager.configs.len())
+                    } else {
+                        format!(
+                            "{} of {}",
+                            app.listed.len(),
+                            app.config_manager.configs.len()
+                        )
+                    },
                     Style::default()
                         .fg(Color::Green)
                         .add_modifier(Modifier::BOLD),
@@ -208,12 +422,26 @@ fn render_header(f: &mut Frame, app: &mut App, area: Rect) {
             Line::from(vec![
                 Span::styled("selected: ", Style::default().fg(Color::Gray)),
                 Span::styled(
-                    format!("{}/{}", selected, app.config_manager.configs.len()),
+                    format!("{}/{}", selected, app.listed.len()),
                     Style::default()
                         .fg(Color::Blue)
                         .add_modifier(Modifier::BOLD),
                 ),
             ]),
+            Line::from(vec![
+                Span::styled("active: ", Style::default().fg(Color::Gray)),
+                match &app.active_profile {
+                    Some(active) => Span::styled(
+                        format!("{}{}", active.name, if active.modified { "*" } else { "" }),
+                        Style::default().fg(if active.modified {
+                            Color::Yellow
+                        } else {
+                            Color::Green
+                        }),
+                    ),
+                    None => Span::styled("none", Style::default().fg(Color::DarkGray)),
+                },
+            ]),
         ]
     };
 
@@ -239,7 +467,11 @@ fn render_main_content(f: &mut Frame, app: &mut App, area: Rect) {
             Line::from(vec![
                 Span::styled("  $ ", Style::default().fg(Color::Cyan)),
                 Span::styled(
-                    "cp ~/.tmux.conf ~/.config/tmucks/default.conf",
+                    format!(
+                        "cp {} {}/default.conf",
+                        app.config_manager.target_display(),
+                        app.config_manager.store_display()
+                    ),
                     Style::default().fg(Color::Green),
                 ),
             ]),
@@ -271,11 +503,11 @@ fn render_main_content(f: &mut Frame, app: &mut App, area: Rect) {
         f.render_widget(empty_message, area);
     } else {
         let items: Vec<ListItem> = app
-            .config_manager
-            .configs
+            .listed
             .iter()
             .enumerate()
-            .map(|(i, name)| {
+            .map(|(i, listed)| {
+                let name = &listed.name;
                 let is_selected = app.list_state.selected() == Some(i);
                 let (icon, style) = if is_selected {
                     (
@@ -288,35 +520,53 @@ fn render_main_content(f: &mut Frame, app: &mut App, area: Rect) {
                     ("  ", Style::default().fg(Color::White))
                 };
 
-                let content = Line::from(vec![
-                    Span::styled(icon, style),
-                    Span::raw(" "),
-                    Span::styled(
-                        name,
-                        Style::default()
-                            .fg(if name.ends_with(".conf") {
-                                Color::Cyan
-                            } else {
-                                Color::White
-                            })
-                            .add_modifier(if is_selected {
-                                Modifier::BOLD
-                            } else {
-                                Modifier::empty()
-                            }),
-                    ),
-                ]);
+                let name_style = Style::default()
+                    .fg(if name.ends_with(".conf") {
+                        Color::Cyan
+                    } else {
+                        Color::White
+                    })
+                    .add_modifier(if is_selected {
+                        Modifier::BOLD
+                    } else {
+                        Modifier::empty()
+                    });
+                let mut spans = vec![Span::styled(icon, style), Span::raw(" ")];
+                spans.extend(highlight_matches(name, &listed.positions, name_style));
+
+                if let Some(active) = app
+                    .active_profile
+                    .as_ref()
+                    .filter(|active| &active.name == name)
+                {
+                    spans.push(Span::styled(" ● active", Style::default().fg(Color::Green)));
+                    if active.modified {
+                        spans.push(Span::styled(
+                            " (modified since apply)",
+                            Style::default().fg(Color::Yellow),
+                        ));
+                    }
+                }
+
+                let content = Line::from(spans);
                 ListItem::new(content)
             })
             .collect();
 
+        let title = if app.search_query.is_empty() {
+            String::from(" configurations ")
+        } else if app.listed.is_empty() {
+            format!(" configurations: no matches for /{} ", app.search_query)
+        } else {
+            format!(" configurations: /{} ", app.search_query)
+        };
         let list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::Blue))
                     .border_type(BorderType::Rounded)
-                    .title(" configurations ")
+                    .title(title)
                     .title_style(Style::default().fg(Color::Yellow)),
             )
             .highlight_style(
@@ -327,8 +577,256 @@ fn render_main_content(f: &mut Frame, app: &mut App, area: Rect) {
             )
             .highlight_symbol("");
 
-        f.render_stateful_widget(list, area, &mut app.list_state);
+        let content_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(area);
+
+        f.render_stateful_widget(list, content_chunks[0], &mut app.list_state);
+
+        let details = details_lines(app);
+        let side_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(details.len() as u16 + 2), // Details
+                Constraint::Min(0),                           // Preview
+            ])
+            .split(content_chunks[1]);
+        render_details(f, details, side_chunks[0]);
+        render_preview(f, app, side_chunks[1]);
+    }
+}
+
+/// Splits a config name into spans, picking out the characters the search
+/// matched
+fn highlight_matches(name: &str, positions: &[usize], style: Style) -> Vec<Span<'static>> {
+    let matched_style = style.fg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+
+    for (i, c) in name.chars().enumerate() {
+        let matched = positions.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let run_style = if run_matched { matched_style } else { style };
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(
+            run,
+            if run_matched { matched_style } else { style },
+        ));
     }
+    spans
+}
+
+/// The selected config's metadata, one line per recorded field
+fn details_lines(app: &App) -> Vec<Line<'static>> {
+    let meta = match app
+        .selected_config()
+        .map(|name| app.config_manager.config_meta(&name))
+    {
+        Some(Ok(meta)) => meta,
+        Some(Err(e)) => {
+            return vec![Line::from(Span::styled(
+                format!("- error: {}", e),
+                Style::default().fg(Color::Red),
+            ))]
+        }
+        None => return vec![Line::from("")],
+    };
+
+    let field = |label: &str, value: String, color: Color| {
+        Line::from(vec![
+            Span::styled(format!("{}: ", label), Style::default().fg(Color::Gray)),
+            Span::styled(value, Style::default().fg(color)),
+        ])
+    };
+
+    let mut lines = Vec::new();
+    if let Some(description) = meta.description {
+        lines.push(Line::from(Span::styled(
+            description,
+            Style::default().fg(Color::White),
+        )));
+    }
+    if !meta.tags.is_empty() {
+        lines.push(field("tags", meta.tags.join(", "), Color::Magenta));
+    }
+    if let Some(author) = meta.author {
+        lines.push(field("author", author, Color::Cyan));
+    }
+    if let Some(created) = meta.created {
+        lines.push(field("created", created, Color::Green));
+    }
+    if let Some(updated) = meta.updated {
+        lines.push(field("updated", updated, Color::Green));
+    }
+    if let Some(tmux_version) = meta.tmux_version {
+        lines.push(field("tmux", tmux_version, Color::Blue));
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "no details recorded",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines
+}
+
+fn render_details(f: &mut Frame, lines: Vec<Line>, area: Rect) {
+    let details = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray))
+                .border_type(BorderType::Rounded)
+                .title(" details ")
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(details, area);
+}
+
+fn render_preview(f: &mut Frame, app: &mut App, area: Rect) {
+    let (title, preview_text) = match app.selected_config() {
+        Some(config_name) => match app.config_manager.read_config(&config_name) {
+            Ok(contents) => (format!(" {} ", config_name), highlight_conf(&contents)),
+            Err(e) => (
+                String::from(" preview "),
+                vec![Line::from(Span::styled(
+                    format!("- error: {}", e),
+                    Style::default().fg(Color::Red),
+                ))],
+            ),
+        },
+        None => (String::from(" preview "), Vec::new()),
+    };
+
+    let preview = Paragraph::new(preview_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray))
+                .border_type(BorderType::Rounded)
+                .title(title)
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .scroll((app.preview_scroll, 0));
+    f.render_widget(preview, area);
+}
+
+fn render_backups(f: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    let items: Vec<ListItem> = app
+        .backups
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let is_selected = app.backup_list_state.selected() == Some(i);
+            let (icon, style) = if is_selected {
+                (
+                    "▶",
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                ("  ", Style::default().fg(Color::White))
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(icon, style),
+                Span::raw(" "),
+                Span::styled(name, style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Blue))
+            .border_type(BorderType::Rounded)
+            .title(" backups ")
+            .title_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_stateful_widget(list, chunks[0], &mut app.backup_list_state);
+
+    let preview_text = match app.selected_backup() {
+        Some(backup_name) => match app.config_manager.read_backup(backup_name) {
+            Ok(contents) => highlight_conf(&contents),
+            Err(e) => vec![Line::from(format!("- error: {}", e))],
+        },
+        None => vec![Line::from(
+            "no backups yet, one is taken every time a config is applied",
+        )],
+    };
+
+    let preview = Paragraph::new(preview_text)
+        .style(Style::default().fg(Color::Gray))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray))
+                .border_type(BorderType::Rounded)
+                .title(" preview ")
+                .title_style(Style::default().fg(Color::Yellow)),
+        );
+    f.render_widget(preview, chunks[1]);
+}
+
+fn render_servers(f: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = if app.sockets.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "no running tmux servers found",
+            Style::default().fg(Color::Gray),
+        ))]
+    } else {
+        app.sockets
+            .iter()
+            .enumerate()
+            .map(|(i, (socket, checked))| {
+                let is_selected = app.socket_list_state.selected() == Some(i);
+                let (icon, style) = if is_selected {
+                    (
+                        "▶",
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    ("  ", Style::default().fg(Color::White))
+                };
+                let checkbox = if *checked { "[x]" } else { "[ ]" };
+
+                ListItem::new(Line::from(vec![
+                    Span::styled(icon, style),
+                    Span::raw(" "),
+                    Span::styled(checkbox, Style::default().fg(Color::Cyan)),
+                    Span::raw(" "),
+                    Span::styled(socket.display().to_string(), style),
+                ]))
+            })
+            .collect()
+    };
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Blue))
+            .border_type(BorderType::Rounded)
+            .title(" tmux servers to reload ")
+            .title_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_stateful_widget(list, area, &mut app.socket_list_state);
 }
 
 fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
@@ -340,20 +838,65 @@ fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
     // Status / Input area
     let status_content = match app.input_mode {
         InputMode::Normal => app.status_message.clone(),
+        InputMode::Searching => format!("/{}", app.search_query),
         InputMode::Saving => format!("save as: {}", app.input_buffer),
+        InputMode::Renaming | InputMode::Copying => format!(
+            "{}: {}{}",
+            if app.input_mode == InputMode::Renaming {
+                "rename to"
+            } else {
+                "copy as"
+            },
+            app.input_buffer,
+            if app.overwrite_pending {
+                "  (already exists, enter again to overwrite)"
+            } else {
+                ""
+            }
+        ),
         InputMode::UpdateConfirm => {
             if let Some(config_name) = &app.pending_update_config {
-                format!("update '{}' with current ~/.tmux.conf? (y/n)", config_name)
+                format!(
+                    "update '{}' with current {}? (y/n)",
+                    config_name,
+                    app.config_manager.target_display()
+                )
             } else {
                 "no config selected for update".to_string()
             }
         }
+        InputMode::DeleteConfirm => match app.selected_config() {
+            Some(config_name) => format!("move '{}' to the trash? (y/n)", config_name),
+            None => String::from("no config selected"),
+        },
+        InputMode::ReapplyConfirm => match app.selected_config() {
+            Some(config_name) => format!(
+                "{} is the active config, re-apply it and reload tmux? (y/n)",
+                config_name
+            ),
+            None => String::from("no config selected"),
+        },
+        InputMode::Backups | InputMode::Diff | InputMode::ReloadErrors | InputMode::Servers => {
+            app.status_message.clone()
+        }
     };
 
     let status_color = match app.input_mode {
-        InputMode::UpdateConfirm => Color::Yellow,
-        InputMode::Saving => Color::Green,
-        InputMode::Normal => {
+        InputMode::UpdateConfirm | InputMode::ReapplyConfirm => Color::Yellow,
+        InputMode::DeleteConfirm => Color::Red,
+        InputMode::Searching => Color::Cyan,
+        InputMode::Saving | InputMode::Renaming | InputMode::Copying => {
+            if app.overwrite_pending {
+                Color::Yellow
+            } else {
+                Color::Green
+            }
+        }
+        InputMode::Normal
+        | InputMode::Backups
+        | InputMode::Diff
+        | InputMode::ReloadErrors
+        | InputMode::Servers => {
             if app.status_message.starts_with("+") {
                 Color::Green
             } else if app.status_message.starts_with("-") {
@@ -386,6 +929,13 @@ fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" navigate "),
+            Span::styled(
+                "/",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" search "),
             Span::styled(
                 "enter",
                 Style::default()
@@ -407,11 +957,60 @@ fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw(" update "),
+            Span::styled(
+                "e",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" edit "),
+            Span::styled(
+                "r",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" rename "),
+            Span::styled(
+                "c",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" copy "),
             Span::styled(
                 "d",
                 Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
             ),
             Span::raw(" delete "),
+            Span::styled(
+                "v",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" diff "),
+            Span::styled(
+                "z",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" undo "),
+            Span::styled(
+                "b",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" backups "),
+            Span::styled(
+                "t",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" servers "),
             Span::styled(
                 "q",
                 Style::default()
@@ -420,7 +1019,15 @@ fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
             ),
             Span::raw(" quit"),
         ])]
-    } else if app.input_mode == InputMode::Saving {
+    } else if app.input_mode == InputMode::Saving
+        || app.input_mode == InputMode::Renaming
+        || app.input_mode == InputMode::Copying
+    {
+        let action = match app.input_mode {
+            InputMode::Renaming => " rename ",
+            InputMode::Copying => " copy ",
+            _ => " save ",
+        };
         vec![Line::from(vec![
             Span::styled(
                 "enter",
@@ -428,15 +1035,90 @@ fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
                     .fg(Color::Green)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw(" save "),
+            Span::raw(action),
             Span::styled(
                 "esc",
                 Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
             ),
             Span::raw(" cancel"),
         ])]
+    } else if app.input_mode == InputMode::Searching {
+        vec![Line::from(vec![
+            Span::styled(
+                "#tag",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" by tag "),
+            Span::styled(
+                "enter",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" keep "),
+            Span::styled(
+                "esc",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" clear"),
+        ])]
+    } else if app.input_mode == InputMode::Diff {
+        vec![Line::from(vec![
+            Span::styled(
+                "j/k",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" scroll "),
+            Span::styled(
+                "esc",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" back"),
+        ])]
+    } else if app.input_mode == InputMode::Backups {
+        vec![Line::from(vec![
+            Span::styled(
+                "enter",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" restore "),
+            Span::styled(
+                "esc",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" back"),
+        ])]
+    } else if app.input_mode == InputMode::Servers {
+        vec![Line::from(vec![
+            Span::styled(
+                "space",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" toggle "),
+            Span::styled(
+                "esc",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" done"),
+        ])]
+    } else if app.input_mode == InputMode::ReloadErrors {
+        vec![Line::from(vec![
+            Span::styled(
+                "esc",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" close"),
+        ])]
     } else {
-        // UpdateConfirm
+        // UpdateConfirm, ReapplyConfirm and DeleteConfirm
         vec![Line::from(vec![
             Span::styled(
                 "y",
@@ -468,87 +1150,287 @@ fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn render_update_popup(f: &mut Frame, app: &mut App) {
-    let popup_area = centered_rect(60, 25, f.size());
+    let popup_area = centered_rect(70, 70, f.size());
 
     // Create a subtle background overlay
     let background = Block::default().style(Style::default().bg(Color::Black));
     f.render_widget(background, f.size());
 
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .border_type(BorderType::Thick)
+        .title(" confirmation ")
+        .title_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .padding(Padding::new(1, 1, 1, 0))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    let inner_area = block.inner(popup_area);
+
+    f.render_widget(Clear, popup_area); // Clear the area for the popup
+    f.render_widget(block, popup_area);
+
+    let Some(config_name) = &app.pending_update_config else {
+        let popup = Paragraph::new(vec![
+            Line::from("no config selected"),
+            Line::from("press any key to continue"),
+        ])
+        .alignment(Alignment::Center);
+        f.render_widget(popup, inner_area);
+        return;
+    };
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5), // Summary
+            Constraint::Min(0),    // Diff
+            Constraint::Length(1), // Keys
+        ])
+        .split(inner_area);
+
     // Popup content
-    let popup_content = if let Some(config_name) = &app.pending_update_config {
-        vec![
-            Line::from(""),
-            Line::from(vec![Span::styled(
-                "confirm update",
+    let summary = Paragraph::new(vec![
+        Line::from(vec![Span::styled(
+            "confirm update",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("config: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                config_name,
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD),
-            )]),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("config: ", Style::default().fg(Color::Gray)),
-                Span::styled(
-                    config_name,
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]),
-            Line::from(""),
-            Line::from("this will overwrite the saved config with:"),
-            Line::from(vec![Span::styled(
-                "~/.tmux.conf",
+            ),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("this will overwrite the saved config with "),
+            Span::styled(
+                app.config_manager.target_display(),
                 Style::default().fg(Color::Green),
-            )]),
-            Line::from(""),
-            Line::from(""),
+            ),
+        ]),
+    ])
+    .alignment(Alignment::Center);
+    f.render_widget(summary, popup_chunks[0]);
+
+    let diff = Paragraph::new(diff_text(&app.diff_lines))
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(Color::Gray))
+                .title(" changes ")
+                .title_style(Style::default().fg(Color::Yellow)),
+        )
+        .scroll((app.diff_scroll, 0));
+    f.render_widget(diff, popup_chunks[1]);
+
+    let keys = Paragraph::new(Line::from(vec![
+        Span::styled("[", Style::default().fg(Color::Gray)),
+        Span::styled(
+            "y",
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("]es", Style::default().fg(Color::White)),
+        Span::raw("  "),
+        Span::styled("[", Style::default().fg(Color::Gray)),
+        Span::styled(
+            "n",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("]o", Style::default().fg(Color::White)),
+        Span::raw("  "),
+        Span::styled("[esc]", Style::default().fg(Color::Red)),
+        Span::raw("  "),
+        Span::styled("[j/k]", Style::default().fg(Color::Cyan)),
+        Span::styled(" scroll", Style::default().fg(Color::White)),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(keys, popup_chunks[2]);
+}
+
+fn render_delete_popup(f: &mut Frame, app: &mut App) {
+    let popup_area = centered_rect(50, 30, f.size());
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .border_type(BorderType::Thick)
+        .title(" confirmation ")
+        .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        .padding(Padding::new(1, 1, 1, 0))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    let inner_area = block.inner(popup_area);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let config_name = app.selected_config().unwrap_or_default();
+
+    let popup = Paragraph::new(vec![
+        Line::from(vec![Span::styled(
+            "confirm delete",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("config: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                config_name,
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+        Line::from("it will be moved to the trash in the store"),
+        Line::from(vec![
+            Span::raw("and can be restored with "),
+            Span::styled("tmucks trash restore", Style::default().fg(Color::Green)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[", Style::default().fg(Color::Gray)),
+            Span::styled(
+                "y",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("]es", Style::default().fg(Color::White)),
+            Span::raw("  "),
+            Span::styled("[", Style::default().fg(Color::Gray)),
+            Span::styled(
+                "n",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("]o", Style::default().fg(Color::White)),
+            Span::raw("  "),
+            Span::styled("[esc]", Style::default().fg(Color::Red)),
+        ]),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    f.render_widget(popup, inner_area);
+}
+
+fn render_reload_errors_popup(f: &mut Frame, app: &mut App) {
+    let popup_area = centered_rect(70, 60, f.size());
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .border_type(BorderType::Thick)
+        .title(" tmux errors ")
+        .title_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        .padding(Padding::new(1, 1, 1, 0))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    let inner_area = block.inner(popup_area);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Summary
+            Constraint::Min(0),    // Errors
+            Constraint::Length(1), // Keys
+        ])
+        .split(inner_area);
+
+    let summary = Paragraph::new(Line::from(vec![
+        Span::raw("tmux rejected parts of "),
+        Span::styled(
+            app.config_manager.target_display(),
+            Style::default().fg(Color::Green),
+        ),
+        Span::raw(" while reloading"),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(summary, popup_chunks[0]);
+
+    let errors: Vec<Line> = app
+        .reload_errors
+        .iter()
+        .map(|issue| {
+            let location = match issue.line {
+                Some(line) => format!("line {}: ", line),
+                None => String::new(),
+            };
             Line::from(vec![
-                Span::styled("[", Style::default().fg(Color::Gray)),
-                Span::styled(
-                    "y",
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Span::styled("]es", Style::default().fg(Color::White)),
-                Span::raw("  "),
-                Span::styled("[", Style::default().fg(Color::Gray)),
-                Span::styled(
-                    "n",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                ),
-                Span::styled("]o", Style::default().fg(Color::White)),
-                Span::raw("  "),
-                Span::styled("[esc]", Style::default().fg(Color::Red)),
-            ]),
-        ]
-    } else {
-        vec![
-            Line::from(""),
-            Line::from("no config selected"),
-            Line::from("press any key to continue"),
-        ]
+                Span::styled(location, Style::default().fg(Color::Cyan)),
+                Span::raw(issue.message.clone()),
+            ])
+        })
+        .collect();
+    let errors = Paragraph::new(errors).wrap(Wrap { trim: false });
+    f.render_widget(errors, popup_chunks[1]);
+
+    let keys = Paragraph::new(Line::from(vec![
+        Span::styled("[esc]", Style::default().fg(Color::Red)),
+        Span::styled(" close", Style::default().fg(Color::White)),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(keys, popup_chunks[2]);
+}
+
+fn render_diff(f: &mut Frame, app: &mut App, area: Rect) {
+    let title = match app.selected_config() {
+        Some(config_name) => format!(
+            " diff: {} -> {} ",
+            config_name,
+            app.config_manager.target_display()
+        ),
+        None => String::from(" diff "),
     };
 
-    let popup = Paragraph::new(popup_content)
+    let diff = Paragraph::new(diff_text(&app.diff_lines))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-                .border_type(BorderType::Thick)
-                .title(" confirmation ")
-                .title_style(
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                )
-                .padding(Padding::new(1, 0, 1, 0)),
+                .border_style(Style::default().fg(Color::Blue))
+                .border_type(BorderType::Rounded)
+                .title(title)
+                .title_style(Style::default().fg(Color::Yellow)),
         )
-        .style(Style::default().bg(Color::Black).fg(Color::White))
-        .alignment(Alignment::Center);
+        .scroll((app.diff_scroll, 0));
+    f.render_widget(diff, area);
+}
 
-    f.render_widget(Clear, popup_area); // Clear the area for the popup
-    f.render_widget(popup, popup_area);
+fn diff_text(lines: &[DiffLine]) -> Vec<Line<'_>> {
+    if lines.is_empty() {
+        return vec![Line::from(Span::styled(
+            "no differences",
+            Style::default().fg(Color::Gray),
+        ))];
+    }
+
+    lines
+        .iter()
+        .map(|line| {
+            let style = match line.kind {
+                DiffKind::Header => Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                DiffKind::Hunk => Style::default().fg(Color::Cyan),
+                DiffKind::Context => Style::default().fg(Color::Gray),
+                DiffKind::Added => Style::default().fg(Color::Green),
+                DiffKind::Removed => Style::default().fg(Color::Red),
+            };
+            Line::from(Span::styled(line.text.as_str(), style))
+        })
+        .collect()
 }
 
 fn centered_rect(